@@ -22,7 +22,7 @@ use nuhound::{
 };
 
 fn get_some() -> Report<u32> {
-    let short_vector = vec![9, 8 ,7];
+    let short_vector = [9, 8 ,7];
     // The get returns an Option and easy converts it to a Result.
     // If the get returns None then an error is generated.
     let value = *short_vector.get(5).report(|e| here!(e, "Index out of bounds"))?;
@@ -0,0 +1,55 @@
+//! Builds the `context_block` macro that wraps any error produced by a block
+
+use proc_macro::{Delimiter, Group, TokenStream, TokenTree};
+use crate::function::{code, tag_sites};
+
+// The message and block given to the macro
+pub(crate) struct Block {
+    message: TokenStream,
+    body: Group,
+}
+
+// Separates the macro input into the message and the block that must be the final argument.
+pub(crate) fn parse_block(item: TokenStream) -> Result<Block, String> {
+    let mut tokens: Vec<TokenTree> = item.into_iter().collect();
+    let body = match tokens.pop() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group,
+        _ => return Err("The final parameter must be a block e.g. context_block!(\"parsing {name}\", { ... })".to_string()),
+    };
+    if matches!(tokens.last(), Some(TokenTree::Punct(punct)) if punct.as_char() == ',') {
+        tokens.pop();
+    }
+    if tokens.is_empty() {
+        return Err("Contains insufficient parameters".to_string());
+    }
+    Ok(Block { message: tokens.into_iter().collect(), body })
+}
+
+// Runs the block inside a closure and wraps any error once with the message and, when the
+// disclose feature is enabled, the location of the macro. A block that uses the '?' operator has
+// each operand converted to a Nuhound and must evaluate to a Report<T>, otherwise the value of the
+// block is converted in the same way as the easy() method.
+pub(crate) fn block_builder(block: Block) -> TokenStream {
+    let Block { message, body } = block;
+    let mut sites = 0;
+    let mut closure_body = Group::new(Delimiter::Brace, tag_sites(body.stream(), "\"\", false", &mut sites));
+    closure_body.set_span(body.span());
+
+    let mut closure = code("||");
+    if sites > 0 {
+        closure.extend(code("-> ::nuhound::Report<_>"));
+    }
+    closure.extend([TokenTree::Group(closure_body)]);
+
+    let mut inner = code("#[allow(unused_imports)] use ::nuhound::__private::Site as _;");
+    inner.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, closure))]);
+    inner.extend(code(&format!("
+    ().nuhound_site(\"\", false).map_err(|cause| {{
+        let link = ::nuhound::Nuhound::new(::std::format!({0}));
+        #[cfg(feature = \"disclose\")]
+        let link = ::nuhound::__private::disclose(link);
+        link.caused_by(cause)
+    }})
+    ", message)));
+    [TokenTree::Group(Group::new(Delimiter::Brace, inner))].into_iter().collect()
+}
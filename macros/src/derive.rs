@@ -0,0 +1,278 @@
+//! Builds the `NuhoundError` derive that converts an error enum into a Nuhound
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+use crate::function::{code, is_ident, is_punct};
+
+// The parts of the enum that the derive needs
+pub(crate) struct Enum {
+    name: String,
+    variants: Vec<Variant>,
+}
+
+// A variant together with the settings given by its 'nuhound' attribute
+struct Variant {
+    name: String,
+    shape: Shape,
+    message: String,
+    arguments: String,
+    kind: String,
+    code: String,
+    source: Option<String>,
+}
+
+// The field layout of a variant. Tuple fields are bound as _0, _1 and so on.
+enum Shape {
+    Unit,
+    Tuple(usize),
+    Named(Vec<String>),
+}
+
+// Splits a token stream at the top level commas. Commas inside the angle brackets of a type such
+// as 'HashMap<K, V>' don't split the stream.
+fn split_commas(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth = 0_usize;
+    for token in stream {
+        let last = parts.last_mut().unwrap();
+        if is_punct(Some(&token), '<') {
+            depth += 1;
+        } else if is_punct(Some(&token), '>') && !is_punct(last.last(), '-') {
+            depth = depth.saturating_sub(1);
+        } else if is_punct(Some(&token), ',') && depth == 0 {
+            parts.push(Vec::new());
+            continue;
+        }
+        last.push(token);
+    }
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+// Removes the leading attributes returning the content of each one
+fn take_attributes(tokens: &[TokenTree]) -> (Vec<TokenStream>, &[TokenTree]) {
+    let mut attributes = Vec::new();
+    let mut index = 0;
+    while is_punct(tokens.get(index), '#') {
+        match tokens.get(index + 1) {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => attributes.push(group.stream()),
+            _ => break,
+        }
+        index += 2;
+    }
+    (attributes, &tokens[index..])
+}
+
+// Separates the enum into its name and variants.
+pub(crate) fn parse_enum(item: TokenStream) -> Result<Enum, String> {
+    let tokens: Vec<TokenTree> = item.into_iter().collect();
+    let position = tokens.iter().position(|token| is_ident(Some(token), "enum"))
+        .ok_or("NuhoundError can only be derived for an enum")?;
+    let name = match tokens.get(position + 1) {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("NuhoundError can only be derived for an enum".to_string()),
+    };
+    let body = match tokens.get(position + 2) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group.stream(),
+        _ => return Err("NuhoundError cannot be derived for a generic enum".to_string()),
+    };
+
+    let mut variants = Vec::new();
+    for part in split_commas(body) {
+        let (attributes, rest) = take_attributes(&part);
+        let variant = match rest.first() {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            _ => return Err(format!("Unexpected variant in enum {}", name)),
+        };
+        let (shape, source) = match rest.get(1) {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => parse_fields(group.stream(), false)?,
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => parse_fields(group.stream(), true)?,
+            _ => (Shape::Unit, None),
+        };
+        let mut parsed = Variant {
+            message: format!("{:?}", variant),
+            arguments: String::new(),
+            kind: "::core::option::Option::None".to_string(),
+            code: format!("{:?}", format!("{}::{}", name, variant)),
+            name: variant,
+            shape,
+            source,
+        };
+        for attribute in attributes {
+            let attribute: Vec<TokenTree> = attribute.into_iter().collect();
+            if let (true, Some(TokenTree::Group(group))) = (is_ident(attribute.first(), "nuhound"), attribute.get(1)) {
+                parse_settings(group.stream(), &mut parsed)?;
+            }
+        }
+        if matches!(parsed.shape, Shape::Tuple(_)) {
+            parsed.message = number_arguments(&parsed.message);
+        }
+        variants.push(parsed);
+    }
+    Ok(Enum { name, variants })
+}
+
+// Reads the fields of a variant returning its shape and the name or position of the source field.
+// The source is the field marked with '#[source]' or, failing that, a named field called 'source'.
+fn parse_fields(stream: TokenStream, named: bool) -> Result<(Shape, Option<String>), String> {
+    let mut names = Vec::new();
+    let mut marked = None;
+    let fields = split_commas(stream);
+    for (index, field) in fields.iter().enumerate() {
+        let (attributes, mut rest) = take_attributes(field);
+        if is_ident(rest.first(), "pub") {
+            rest = match rest.get(1) {
+                Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => &rest[2..],
+                _ => &rest[1..],
+            };
+        }
+        let name = match (named, rest.first()) {
+            (true, Some(TokenTree::Ident(ident))) => ident.to_string(),
+            (true, _) => return Err("Unexpected field in variant".to_string()),
+            (false, _) => index.to_string(),
+        };
+        let is_source = attributes.iter().any(|attribute| {
+            let attribute: Vec<TokenTree> = attribute.clone().into_iter().collect();
+            attribute.len() == 1 && is_ident(attribute.first(), "source")
+        });
+        if is_source {
+            if marked.is_some() {
+                return Err("A variant can only have one #[source] field".to_string());
+            }
+            marked = Some(name.clone());
+        }
+        names.push(name);
+    }
+    if named {
+        let source = marked.or_else(|| names.iter().find(|name| *name == "source").cloned());
+        Ok((Shape::Named(names), source))
+    } else {
+        Ok((Shape::Tuple(names.len()), marked))
+    }
+}
+
+// Reads the settings of a 'nuhound' attribute such as
+// '#[nuhound(message = "file {path} missing", kind = NotFound, code = "CFG001")]'. The message may
+// be followed by format arguments in the same way as `format!`.
+fn parse_settings(stream: TokenStream, variant: &mut Variant) -> Result<(), String> {
+    for setting in split_commas(stream) {
+        // Anything other than a setting is an argument of the message
+        if !(matches!(setting.first(), Some(TokenTree::Ident(_))) && is_punct(setting.get(1), '=') && !is_punct(setting.get(2), '=')) {
+            let argument: TokenStream = setting.into_iter().collect();
+            variant.arguments += &format!(", {}", argument);
+            continue;
+        }
+        let value = match (setting.first(), is_punct(setting.get(1), '='), setting.get(2), setting.len()) {
+            (Some(TokenTree::Ident(key)), true, Some(value), 3) => (key.to_string(), value),
+            _ => return Err("Expected a setting such as message = \"...\", kind = NotFound or code = \"E001\"".to_string()),
+        };
+        match value {
+            (key, TokenTree::Literal(literal)) if key == "message" => variant.message = literal.to_string(),
+            (key, TokenTree::Ident(ident)) if key == "kind" => {
+                variant.kind = format!("::core::option::Option::Some(::nuhound::Kind::{})", ident);
+            },
+            (key, TokenTree::Literal(literal)) if key == "code" => {
+                let text = literal.to_string();
+                variant.code = if text.starts_with('"') { text } else { format!("{:?}", text) };
+            },
+            (key, _) => return Err(format!("Unexpected value for the nuhound setting '{}'", key)),
+        }
+    }
+    Ok(())
+}
+
+// Rewrites the positional arguments of a tuple variant message, such as '{0}' or '{1:?}', so that
+// they refer to the bound fields _0, _1 and so on.
+fn number_arguments(message: &str) -> String {
+    let mut result = String::new();
+    let mut chars = message.chars().peekable();
+    while let Some(ch) = chars.next() {
+        result.push(ch);
+        if ch == '{' {
+            match chars.peek() {
+                Some('{') => result.push(chars.next().unwrap()),
+                Some(digit) if digit.is_ascii_digit() => result.push('_'),
+                _ => (),
+            }
+        }
+    }
+    result
+}
+
+// Returns a pattern binding every field of the variant
+fn bind_all(name: &str, variant: &Variant) -> String {
+    match &variant.shape {
+        Shape::Unit => format!("{}::{}", name, variant.name),
+        Shape::Tuple(count) => {
+            let fields: Vec<String> = (0..*count).map(|index| format!("_{}", index)).collect();
+            format!("{}::{}({})", name, variant.name, fields.join(", "))
+        },
+        Shape::Named(fields) => format!("{}::{} {{ {} }}", name, variant.name, fields.join(", ")),
+    }
+}
+
+// Returns a pattern binding the source field of the variant as __nuhound_source
+fn bind_source(name: &str, variant: &Variant, source: &str) -> String {
+    match &variant.shape {
+        Shape::Named(_) => format!("{}::{} {{ {}: __nuhound_source, .. }}", name, variant.name, source),
+        _ => format!("{}::{}({}__nuhound_source, ..)", name, variant.name, "_, ".repeat(source.parse().unwrap())),
+    }
+}
+
+// Implements Display and Error for the enum together with a conversion into a Nuhound that keeps
+// the kind and code of the variant and the chain of its source.
+pub(crate) fn derive_builder(parsed: Enum) -> TokenStream {
+    let name = &parsed.name;
+    let mut display = String::new();
+    let mut sources = String::new();
+    let mut settings = String::new();
+    let mut chains = String::new();
+    for variant in &parsed.variants {
+        display += &format!("{} => ::core::write!(f, {}{}),\n", bind_all(name, variant), variant.message, variant.arguments);
+        settings += &format!("{}::{} {{ .. }} => ({}, {}),\n", name, variant.name, variant.kind, variant.code);
+        if let Some(source) = &variant.source {
+            let pattern = bind_source(name, variant, source);
+            sources += &format!("{} => __nuhound_source.nuhound_source(),\n", pattern);
+            chains += &format!("{} => link.caused_by(__nuhound_source.nuhound_chain()),\n", pattern);
+        }
+    }
+
+    code(&format!("
+    impl ::core::fmt::Display for {name} {{
+        #[allow(unused_variables)]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{
+            match self {{
+                {display}
+            }}
+        }}
+    }}
+
+    impl ::std::error::Error for {name} {{
+        #[allow(unreachable_patterns)]
+        fn source(&self) -> ::core::option::Option<&(dyn ::std::error::Error + 'static)> {{
+            #[allow(unused_imports)]
+            use ::nuhound::__private::{{SourceOfBoxed, SourceOfError}};
+            match self {{
+                {sources}
+                _ => ::core::option::Option::None,
+            }}
+        }}
+    }}
+
+    impl ::core::convert::From<{name}> for ::nuhound::Nuhound {{
+        #[track_caller]
+        #[allow(unreachable_patterns)]
+        fn from(value: {name}) -> ::nuhound::Nuhound {{
+            #[allow(unused_imports)]
+            use ::nuhound::__private::{{ChainOfNuhound, ChainOfError}};
+            let (kind, code) = match &value {{
+                {settings}
+            }};
+            let link = ::nuhound::__private::derived(&value, kind, code, ::core::panic::Location::caller());
+            match &value {{
+                {chains}
+                _ => link,
+            }}
+        }}
+    }}
+    "))
+}
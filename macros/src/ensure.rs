@@ -0,0 +1,146 @@
+//! Builds the `ensure` macro that returns early when a condition is false
+
+use proc_macro::{Spacing, TokenStream, TokenTree};
+use crate::function::{code, is_punct};
+
+// The condition and optional message given to the macro
+pub(crate) struct Ensure {
+    condition: Vec<TokenTree>,
+    message: Option<TokenStream>,
+}
+
+// A condition that compares two operands
+struct Comparison {
+    left: TokenStream,
+    operator: &'static str,
+    right: TokenStream,
+}
+
+// Determines whether the token is punctuation joined to the token that follows it
+fn is_joint(token: Option<&TokenTree>, ch: char) -> bool {
+    matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == ch && punct.spacing() == Spacing::Joint)
+}
+
+// Tracks the angle brackets of turbofish generics such as 'Vec::<u8>::new()' so that they are not
+// mistaken for comparisons or argument separators. Returns true if the token at 'index' is part of
+// the generics.
+fn in_generics(tokens: &[TokenTree], index: usize, depth: &mut usize) -> bool {
+    let token = Some(&tokens[index]);
+    if is_punct(token, '<') && index >= 2 && is_joint(tokens.get(index - 2), ':') && is_punct(tokens.get(index - 1), ':') {
+        *depth += 1;
+        return true;
+    }
+    if *depth > 0 {
+        if is_punct(token, '<') {
+            *depth += 1;
+        } else if is_punct(token, '>') && !is_joint(tokens.get(index.wrapping_sub(1)), '-') {
+            *depth -= 1;
+        }
+        return true;
+    }
+    false
+}
+
+// Separates the macro input into the condition and the optional message.
+pub(crate) fn parse_ensure(item: TokenStream) -> Result<Ensure, String> {
+    let tokens: Vec<TokenTree> = item.into_iter().collect();
+    let mut depth = 0;
+    let mut index = 0;
+    while index < tokens.len() {
+        if !in_generics(&tokens, index, &mut depth) && is_punct(Some(&tokens[index]), ',') {
+            break;
+        }
+        index += 1;
+    }
+    let condition: Vec<TokenTree> = tokens[..index].to_vec();
+    if condition.is_empty() {
+        return Err("The ensure macro requires a condition e.g. ensure!(count > 0, \"no items\")".to_string());
+    }
+    let mut message: Vec<TokenTree> = tokens.into_iter().skip(index + 1).collect();
+    if is_punct(message.last(), ',') {
+        message.pop();
+    }
+    let message: TokenStream = message.into_iter().collect();
+    let message = if message.is_empty() { None } else { Some(message) };
+    Ok(Ensure { condition, message })
+}
+
+// Splits a condition containing a single comparison into its operands. Conditions combined with
+// '&&' or '||', or containing more than one comparison, are left whole.
+fn comparison(tokens: &[TokenTree]) -> Option<Comparison> {
+    let mut found = None;
+    let mut depth = 0;
+    let mut index = 0;
+    while index < tokens.len() {
+        if in_generics(tokens, index, &mut depth) {
+            index += 1;
+            continue;
+        }
+        let token = Some(&tokens[index]);
+        let next = tokens.get(index + 1);
+        let (operator, width) = if (is_joint(token, '&') && is_punct(next, '&'))
+            || (is_joint(token, '|') && is_punct(next, '|')) {
+            return None;
+        } else if is_joint(token, '=') && is_punct(next, '=') {
+            ("==", 2)
+        } else if is_joint(token, '!') && is_punct(next, '=') {
+            ("!=", 2)
+        } else if (is_joint(token, '<') && is_punct(next, '<')) || (is_joint(token, '>') && is_punct(next, '>')) {
+            // A shift operator
+            index += 2;
+            continue;
+        } else if is_joint(token, '<') && is_punct(next, '=') {
+            ("<=", 2)
+        } else if is_joint(token, '>') && is_punct(next, '=') {
+            (">=", 2)
+        } else if is_punct(token, '<') {
+            ("<", 1)
+        } else if is_punct(token, '>') && !(index > 0 && (is_joint(tokens.get(index - 1), '-') || is_joint(tokens.get(index - 1), '='))) {
+            (">", 1)
+        } else {
+            index += 1;
+            continue;
+        };
+        if found.is_some() {
+            return None;
+        }
+        found = Some((index, operator, width));
+        index += width;
+    }
+    let (index, operator, width) = found?;
+    Some(Comparison {
+        left: tokens[..index].iter().cloned().collect(),
+        operator,
+        right: tokens[index + width..].iter().cloned().collect(),
+    })
+}
+
+// Returns early with a located Nuhound when the condition is false. A message given to the macro
+// is used as is, otherwise the message shows the condition and, for a comparison, the value of
+// each operand.
+pub(crate) fn ensure_builder(ensure: Ensure) -> TokenStream {
+    let Ensure { condition, message } = ensure;
+    let text: TokenStream = condition.iter().cloned().collect();
+    let fail = |inform: String| format!(
+        "return ::core::result::Result::Err(::core::convert::From::from(::nuhound::here!(Root, {})));",
+        inform
+    );
+    match (message, comparison(&condition)) {
+        (Some(message), _) => code(&format!("if !({}) {{ {} }}", text, fail(message.to_string()))),
+        (None, Some(Comparison { left, operator, right })) => code(&format!("
+            match (&({0}), &({1})) {{
+                (left, right) => if !(*left {2} *right) {{
+                    {3}
+                }}
+            }}",
+            left,
+            right,
+            operator,
+            fail(format!("\"condition failed: `{{}}` (left: {{:?}}, right: {{:?}})\", ::core::stringify!({}), left, right", text)),
+        )),
+        (None, None) => code(&format!("if !({}) {{ {} }}",
+            text,
+            fail(format!("\"condition failed: `{{}}`\", ::core::stringify!({})", text)),
+        )),
+    }
+}
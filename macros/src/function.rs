@@ -0,0 +1,220 @@
+//! Builds the `context` attribute that wraps every error leaving a function
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+// The parts of the function that the attribute needs to rebuild it
+pub(crate) struct Function {
+    head: Vec<TokenTree>,
+    name: String,
+    output: Vec<TokenTree>,
+    body: Group,
+}
+
+// The message and mode given to the attribute
+pub(crate) struct Arguments {
+    each: bool,
+    message: TokenStream,
+}
+
+// Determines whether the token is the given punctuation character
+pub(crate) fn is_punct(token: Option<&TokenTree>, ch: char) -> bool {
+    matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == ch)
+}
+
+// Determines whether the token is the given identifier
+pub(crate) fn is_ident(token: Option<&TokenTree>, name: &str) -> bool {
+    matches!(token, Some(TokenTree::Ident(ident)) if ident.to_string() == name)
+}
+
+// Returns the index of the token following the angle bracketed generics starting at 'index'. The
+// arrow of a closure or function trait bound is not mistaken for a closing bracket.
+fn skip_generics(tokens: &[TokenTree], mut index: usize) -> usize {
+    let mut depth = 0;
+    while index < tokens.len() {
+        let token = Some(&tokens[index]);
+        if is_punct(token, '<') {
+            depth += 1;
+        } else if is_punct(token, '>') && !(index > 0 && is_punct(Some(&tokens[index - 1]), '-')) {
+            depth -= 1;
+            if depth == 0 {
+                return index + 1;
+            }
+        }
+        index += 1;
+    }
+    index
+}
+
+// Separates the attribute arguments into the optional 'each' mode and the message.
+pub(crate) fn parse_arguments(attr: TokenStream) -> Result<Arguments, String> {
+    let tokens: Vec<TokenTree> = attr.into_iter().collect();
+    let each = is_ident(tokens.first(), "each") && is_punct(tokens.get(1), ',');
+    let message: TokenStream = tokens.into_iter().skip(if each { 2 } else { 0 }).collect();
+    if message.is_empty() {
+        return Err("The context attribute requires a message e.g. #[context(\"loading config {path}\")]".to_string());
+    }
+    Ok(Arguments { each, message })
+}
+
+// Separates the function into the parts needed to rebuild it.
+pub(crate) fn parse_function(item: TokenStream) -> Result<Function, String> {
+    let mut tokens: Vec<TokenTree> = item.into_iter().collect();
+    let body = match tokens.pop() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group,
+        _ => return Err("The context attribute can only be applied to a function".to_string()),
+    };
+
+    let position = tokens.iter().position(|token| is_ident(Some(token), "fn"))
+        .ok_or("The context attribute can only be applied to a function")?;
+    if tokens[..position].iter().any(|token| is_ident(Some(token), "async")) {
+        return Err("The context attribute cannot be applied to an async function".to_string());
+    }
+    let name = match tokens.get(position + 1) {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("The context attribute can only be applied to a function".to_string()),
+    };
+
+    let mut index = position + 2;
+    if is_punct(tokens.get(index), '<') {
+        index = skip_generics(&tokens, index);
+    }
+    // Step over the parameters
+    index += 1;
+    if !(is_punct(tokens.get(index), '-') && is_punct(tokens.get(index + 1), '>')) {
+        return Err("The context attribute requires a function that returns Report<T>".to_string());
+    }
+    index += 2;
+
+    let output = tokens[index..].iter()
+        .take_while(|token| !is_ident(Some(token), "where"))
+        .cloned()
+        .collect();
+
+    Ok(Function { head: tokens, name, output, body })
+}
+
+// Inserts a call to 'nuhound_site' before every '?' operator so that the error is converted to a
+// Nuhound and, when required, the location of the operator is recorded. A '?' that doesn't follow
+// an expression, such as the one in '?Sized', is ignored, as is any '?' in a nested function or
+// closure, which returns from that function or closure rather than from the code being tagged.
+// The number of sites is added to 'count'.
+pub(crate) fn tag_sites(stream: TokenStream, arguments: &str, count: &mut usize) -> TokenStream {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    let mut tagged = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = tokens[index].clone();
+        index += 1;
+        match token {
+            // A nested function is left untouched up to and including its body
+            TokenTree::Ident(ident) if ident.to_string() == "fn" && matches!(tokens.get(index), Some(TokenTree::Ident(_))) => {
+                tagged.push(TokenTree::Ident(ident));
+                let end = tokens[index..].iter()
+                    .position(|token| is_punct(Some(token), ';') || matches!(token, TokenTree::Group(group) if group.delimiter() == Delimiter::Brace))
+                    .map_or(tokens.len(), |offset| index + offset + 1);
+                tagged.extend(tokens[index..end].iter().cloned());
+                index = end;
+            },
+            // A closure is left untouched up to the end of the expression holding it
+            TokenTree::Punct(punct) if punct.as_char() == '|' && opens_closure(tagged.last()) => {
+                tagged.push(TokenTree::Punct(punct));
+                let end = tokens[index..].iter()
+                    .position(|token| is_punct(Some(token), ',') || is_punct(Some(token), ';'))
+                    .map_or(tokens.len(), |offset| index + offset);
+                tagged.extend(tokens[index..end].iter().cloned());
+                index = end;
+            },
+            TokenTree::Group(group) => {
+                let mut replacement = Group::new(group.delimiter(), tag_sites(group.stream(), arguments, count));
+                replacement.set_span(group.span());
+                tagged.push(TokenTree::Group(replacement));
+            },
+            TokenTree::Punct(punct) if punct.as_char() == '?' && matches!(tagged.last(),
+                Some(TokenTree::Ident(_)) | Some(TokenTree::Literal(_)) | Some(TokenTree::Group(_))) => {
+                let span = punct.span();
+                let arguments = code(arguments).into_iter().map(|mut token| { token.set_span(span); token }).collect();
+                let mut dot = Punct::new('.', Spacing::Alone);
+                dot.set_span(span);
+                let mut group = Group::new(Delimiter::Parenthesis, arguments);
+                group.set_span(span);
+                tagged.push(TokenTree::Punct(dot));
+                tagged.push(TokenTree::Ident(Ident::new("nuhound_site", span)));
+                tagged.push(TokenTree::Group(group));
+                tagged.push(TokenTree::Punct(punct));
+                *count += 1;
+            },
+            token => tagged.push(token),
+        }
+    }
+    tagged.into_iter().collect()
+}
+
+// Determines whether a '|' following the token starts the parameters of a closure rather than
+// being a bitwise or logical 'or' applied to the expression that ends with the token.
+fn opens_closure(previous: Option<&TokenTree>) -> bool {
+    match previous {
+        None => true,
+        Some(TokenTree::Ident(ident)) => matches!(ident.to_string().as_str(), "move" | "return" | "break" | "async"),
+        Some(TokenTree::Punct(punct)) => !matches!(punct.as_char(), '?' | '|'),
+        Some(TokenTree::Literal(_)) | Some(TokenTree::Group(_)) => false,
+    }
+}
+
+// Parses a fragment of generated code. Fragments need only be lexically valid.
+pub(crate) fn code(text: &str) -> TokenStream {
+    text.parse().unwrap()
+}
+
+// Rebuilds the function so that its body runs inside a closure whose error is wrapped with the
+// message, the function name and, when the disclose feature is enabled, the attribute location.
+pub(crate) fn context_builder(arguments: Arguments, function: Function) -> TokenStream {
+    let Function { head, name, output, body } = function;
+    let output: TokenStream = output.into_iter().collect();
+    let mut body_stream = body.stream();
+    if arguments.each {
+        let arguments = format!("{:?}, cfg!(feature = \"disclose\")", name);
+        body_stream = tag_sites(body_stream, &arguments, &mut 0);
+    }
+    let mut closure_body = Group::new(Delimiter::Brace, body_stream);
+    closure_body.set_span(body.span());
+
+    let mut inner = TokenStream::new();
+    if arguments.each {
+        inner.extend(code("#[allow(unused_imports)] use ::nuhound::__private::Site as _;"));
+    }
+    inner.extend(code("let __nuhound_result:"));
+    inner.extend(output.clone());
+    let mut closure = code("||->");
+    closure.extend(output);
+    closure.extend([TokenTree::Group(closure_body)]);
+    inner.extend(code("="));
+    inner.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, closure))]);
+    inner.extend(code("(); __nuhound_result.map_err"));
+
+    // The message is only formatted once an error is known to be leaving the function
+    let mut handler = code("let __nuhound_context = ::std::format!");
+    handler.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, arguments.message))]);
+    handler.extend(code(&format!("
+    ;
+    let link = ::nuhound::Nuhound::new(::std::format!(\"{{0}}: {{1}}\", {0:?}, __nuhound_context));
+    #[cfg(feature = \"disclose\")]
+    let link = ::nuhound::__private::disclose(link);
+    link.caused_by(cause)
+    ", name)));
+    let mut map_err = code("|cause|");
+    map_err.extend([TokenTree::Group(Group::new(Delimiter::Brace, handler))]);
+    inner.extend([TokenTree::Group(Group::new(Delimiter::Parenthesis, map_err))]);
+
+    let mut result: TokenStream = head.into_iter().collect();
+    result.extend([TokenTree::Group(Group::new(Delimiter::Brace, inner))]);
+    result
+}
+
+// Reports a problem with the attribute as a compile time error.
+pub(crate) fn compile_error(message: &str) -> TokenStream {
+    let mut result = code("::std::compile_error!");
+    let mut group = Group::new(Delimiter::Parenthesis, TokenTree::Literal(Literal::string(message)).into());
+    group.set_span(Span::call_site());
+    result.extend([TokenTree::Group(group), TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+    result
+}
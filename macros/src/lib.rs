@@ -0,0 +1,223 @@
+//! A Rust library of attribute, function-like and derive macros for nuhound error tracing
+//!
+//! These macros complement the `here!`, `convert!`, `examine!` and `custom!` macros of the nuhound
+//! crate and are re-exported by it. Please refer to the nuhound crate for a fuller explanation of
+//! usage.
+//!
+
+mod function;
+mod block;
+mod ensure;
+mod derive;
+use proc_macro::TokenStream;
+
+//  context attribute
+/// An attribute that adds context to every error returned by a function. The function must
+/// return `Report<T>`.
+///
+/// Any error leaving the function, whether by the `?` operator or by an explicit return, is
+/// wrapped with a message containing the function name and the formatted text given to the
+/// attribute. The text is formatted in the same way as `format!`, but only when an error leaves
+/// the function, so it may refer to any function parameter that the body doesn't move. When the
+/// `disclose` feature is enabled the new link also records the location of the attribute.
+///
+/// Starting the attribute with `each` also records the location of every `?` operator in the
+/// function body. Each `?` must then be applied to a `Result` or `Option` supported by the
+/// nuhound `easy()` method. A `?` inside a nested function or closure is left alone because it
+/// returns from that function or closure.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nuhound::{Report, ResultExtension, context};
+///
+/// #[context("loading config {path}")]
+/// fn load_config(path: &str) -> Report<u32> {
+///     let text = std::fs::read_to_string(path)?;
+///     let value = text.trim().parse::<u32>().easy()?;
+///     Ok(value)
+/// }
+///
+/// #[context(each, "reading the port number")]
+/// fn port() -> Report<u32> {
+///     let value = load_config("xuhgd56qhsl")?;
+///     Ok(value)
+/// }
+///
+/// match port() {
+///     Ok(value) => println!("Value = {value}"),
+///     Err(e) => eprintln!("{}", e.trace()),
+/// }
+///
+/// // using `cargo run --features disclose` will emit the following message:
+/// //
+/// //  0: examples/config.rs:10:1: port: reading the port number
+/// //  1: examples/config.rs:12:38: propagated through port
+/// //  2: examples/config.rs:3:1: load_config: loading config xuhgd56qhsl
+/// //  3: No such file or directory (os error 2)
+///```
+#[proc_macro_attribute]
+pub fn context(attr: TokenStream, item: TokenStream) -> TokenStream {
+    // The function is left untouched when there is a problem so that it is the only error shown
+    let parsed = function::parse_arguments(attr)
+        .and_then(|arguments| Ok((arguments, function::parse_function(item.clone())?)));
+    match parsed {
+        Ok((arguments, parsed)) => function::context_builder(arguments, parsed),
+        Err(message) => {
+            let mut result = function::compile_error(&message);
+            result.extend(item);
+            result
+        },
+    }
+}
+
+//  context_block macro
+/// A macro that runs a block and wraps any error it produces, once, with a message. This replaces
+/// the pattern of wrapping an immediately invoked closure with the `convert!` macro. The macro is
+/// not called `context!` because that name is taken by the `context` attribute.
+///
+/// The message is formatted in the same way as `format!` and, when the `disclose` feature is
+/// enabled, contains the location of the macro.
+///
+/// A block that uses the `?` operator is treated like the body of a function returning
+/// `Report<T>`: the operand of every `?` may be any `Result` or `Option` supported by the nuhound
+/// `easy()` method and the block must evaluate to a `Report<T>`. A block that doesn't use the `?`
+/// operator may evaluate to any `Result` or `Option` supported by the nuhound `easy()` method.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nuhound::{Report, context_block};
+///
+/// fn parse_pair(name: &str, text: &str) -> Report<(u32, u32)> {
+///     let pair = context_block!("parsing {name}", {
+///         let (left, right) = text.split_once(',')?;
+///         Ok((left.trim().parse::<u32>()?, right.trim().parse::<u32>()?))
+///     })?;
+///     Ok(pair)
+/// }
+///
+/// fn my_option() -> Report<u32> {
+///     let vector = vec![0, 1, 2, 3];
+///     let index = 4;
+///     let value = context_block!("Index {index} is out of range", { vector.get(index) })?;
+///     Ok(*value)
+/// }
+///
+/// match parse_pair("size", "12, NaN") {
+///     Ok(value) => println!("Value = {value:?}"),
+///     Err(e) => eprintln!("{}", e.trace()),
+/// }
+///
+/// // using `cargo run --features disclose` will emit the following message:
+/// //
+/// //  0: examples/pair.rs:4:16: parsing size
+/// //  1: invalid digit found in string
+///```
+#[proc_macro]
+pub fn context_block(item: TokenStream) -> TokenStream {
+    match block::parse_block(item) {
+        Ok(parsed) => block::block_builder(parsed),
+        Err(message) => function::compile_error(&message),
+    }
+}
+
+//  ensure macro
+/// A macro that returns early with a Nuhound error when a condition is false. The error is
+/// created in the same way as `here!(Root, ...)` so that, when the `disclose` feature is enabled,
+/// it contains the location of the macro. The error is converted with `From` so that the macro
+/// can be used in any function returning a `Result` whose error can be created from a Nuhound.
+///
+/// A message may follow the condition and is formatted in the same way as `format!`. Without a
+/// message the error shows the text of the condition and, when the condition is a single
+/// comparison such as `a == b` or `count < limit`, the value of each operand. Operands compared
+/// in this way must implement `Debug`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nuhound::{Report, ensure};
+///
+/// fn check(items: &[u32], limit: usize) -> Report<()> {
+///     ensure!(!items.is_empty(), "No items were given");
+///     ensure!(items.len() <= limit);
+///     Ok(())
+/// }
+///
+/// match check(&[1, 2, 3], 2) {
+///     Ok(_) => unreachable!(),
+///     Err(e) => eprintln!("{e}"),
+/// }
+///
+/// // This will emit:
+/// //
+/// // condition failed: `items.len() <= limit` (left: 3, right: 2)
+///```
+#[proc_macro]
+pub fn ensure(item: TokenStream) -> TokenStream {
+    match ensure::parse_ensure(item) {
+        Ok(parsed) => ensure::ensure_builder(parsed),
+        Err(message) => function::compile_error(&message),
+    }
+}
+
+//  NuhoundError derive
+/// A derive that turns an error enum into a Nuhound error while keeping the identity of each
+/// variant. It implements `Display` and `std::error::Error` for the enum and
+/// `From<MyError> for Nuhound`, so that the `?` operator can move from a `Result<T, MyError>` into
+/// a `Report<T>`. The enum must also implement `Debug`.
+///
+/// Each variant may be given a `nuhound` attribute with the following settings, all of which are
+/// optional:
+///
+/// * `message` - the text displayed for the variant. It may refer to named fields as `{path}` and
+///   to tuple fields as `{0}`, and may be followed by format arguments that refer to named fields
+///   such as `message = "{} entries", table.len()`. The variant name is displayed by default;
+/// * `kind` - a member of `nuhound::Kind` that classifies the variant;
+/// * `code` - a string or integer that identifies the variant. The enum and variant name, such as
+///   `ConfigError::Missing`, are used by default.
+///
+/// The cause of a variant is the field marked with `#[source]` or a named field called `source`.
+/// It may be any error type, including `Nuhound` and `Box<dyn Error>`, and its chain is kept in
+/// the resulting Nuhound. When the `disclose` feature is enabled the conversion records the
+/// location of the `?` operator.
+///
+/// # Examples
+///
+/// ```ignore
+/// use nuhound::{Report, NuhoundError};
+///
+/// #[derive(Debug, NuhoundError)]
+/// enum ConfigError {
+///     #[nuhound(message = "config file {path} is missing", kind = NotFound, code = "CFG001")]
+///     Missing { path: String, source: std::io::Error },
+///     #[nuhound(message = "line {0} is not a number", kind = InvalidData, code = 2)]
+///     Invalid(usize, #[source] std::num::ParseIntError),
+///     Empty,
+/// }
+///
+/// fn read(path: &str) -> Result<String, ConfigError> {
+///     std::fs::read_to_string(path).map_err(|source| ConfigError::Missing { path: path.to_string(), source })
+/// }
+///
+/// fn load(path: &str) -> Report<String> {
+///     let text = read(path)?;
+///     Ok(text)
+/// }
+///
+/// let e = load("xuhgd56qhsl").unwrap_err();
+/// assert_eq!(e.code(), Some("CFG001"));
+/// println!("{}", e.trace());
+///
+/// // This will emit:
+/// //
+/// //  0: config file xuhgd56qhsl is missing (code: CFG001, kind: NotFound)
+/// //  1: No such file or directory (os error 2)
+///```
+#[proc_macro_derive(NuhoundError, attributes(nuhound, source))]
+pub fn nuhound_error(item: TokenStream) -> TokenStream {
+    match derive::parse_enum(item) {
+        Ok(parsed) => derive::derive_builder(parsed),
+        Err(message) => function::compile_error(&message),
+    }
+}
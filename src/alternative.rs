@@ -0,0 +1,199 @@
+use std::fmt;
+use std::panic::Location;
+
+use crate::{Nuhound, Report};
+
+/// Tries each alternative in order and returns the first success. If every alternative fails
+/// then a single Nuhound error is returned whose causes hold the failure of each alternative, in
+/// the order they were tried, labelled with the name of the alternative. The alternatives after
+/// the first success are never run.
+///
+/// When the disclose feature is enabled the location of the caller is recorded on the error.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, here, first_ok};
+///
+/// fn from_env() -> Report<u32> {
+///     Err(here!(Root, "PORT is not set"))
+/// }
+///
+/// fn from_file(path: &str) -> Report<u32> {
+///     Err(here!(Root, "{path} does not exist"))
+/// }
+///
+/// let port = first_ok([
+///     ("environment", &from_env as &dyn Fn() -> Report<u32>),
+///     ("user file", &|| from_file("~/.app.cfg")),
+///     ("system file", &|| from_file("/etc/app.cfg")),
+/// ]);
+/// let e = port.unwrap_err();
+/// assert_eq!(e.causes().len(), 3);
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: all 3 alternatives failed
+/// //     cause 1:
+/// //      0: alternative 'environment' failed
+/// //      1: PORT is not set
+/// //     cause 2:
+/// //      0: alternative 'user file' failed
+/// //      1: ~/.app.cfg does not exist
+/// //     cause 3:
+/// //      0: alternative 'system file' failed
+/// //      1: /etc/app.cfg does not exist
+///
+/// let port = first_ok([
+///     ("environment", &from_env as &dyn Fn() -> Report<u32>),
+///     ("defaults", &|| Ok(8080)),
+/// ]);
+/// assert_eq!(port, Ok(8080));
+/// ```
+#[track_caller]
+pub fn first_ok<T, L, F, I>(alternatives: I) -> Report<T>
+where
+    L: fmt::Display,
+    F: FnOnce() -> Report<T>,
+    I: IntoIterator<Item = (L, F)>,
+{
+    aggregate(alternatives.into_iter().map(|(label, op)| (label, op())), Location::caller())
+}
+
+/// Provides `first_ok()` to any iterator of [`Report<T>`]. Iterators are lazy, so the items after
+/// the first success are never produced.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, ResultExtension, AlternativeExtension};
+///
+/// let value = ["NaN", "", "42", "7"].iter().map(|text| text.parse::<u32>().easy()).first_ok();
+/// assert_eq!(value, Ok(42));
+///
+/// let e = ["NaN", ""].iter().map(|text| text.parse::<u32>().easy()).first_ok().unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: all 2 alternatives failed
+/// //     cause 1:
+/// //      0: alternative 1 failed
+/// //      1: invalid digit found in string
+/// //     cause 2:
+/// //      0: alternative 2 failed
+/// //      1: cannot parse integer from empty string
+/// ```
+pub trait AlternativeExtension: Iterator + Sized {
+    /// Returns the first success or an error whose causes hold every failure labelled with its
+    /// position in the iterator, starting at 1.
+    #[track_caller]
+    fn first_ok<T>(self) -> Report<T>
+    where
+        Self: Iterator<Item = Report<T>>;
+
+    /// Returns the first success or an error whose causes hold every failure labelled with the
+    /// label that accompanied it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Report, ResultExtension, AlternativeExtension};
+    ///
+    /// let paths = ["a.cfg", "b.cfg"];
+    /// let e = paths.iter()
+    ///     .map(|path| (path, std::fs::read_to_string(path).easy()))
+    ///     .first_ok_labelled()
+    ///     .unwrap_err();
+    /// assert!(e.causes()[1].to_string().ends_with("alternative 'b.cfg' failed"));
+    /// ```
+    #[track_caller]
+    fn first_ok_labelled<T, L: fmt::Display>(self) -> Report<T>
+    where
+        Self: Iterator<Item = (L, Report<T>)>;
+}
+
+impl<I: Iterator> AlternativeExtension for I {
+    #[track_caller]
+    fn first_ok<T>(self) -> Report<T>
+    where
+        Self: Iterator<Item = Report<T>>,
+    {
+        aggregate(self.enumerate().map(|(index, report)| (index + 1, report)), Location::caller())
+    }
+
+    #[track_caller]
+    fn first_ok_labelled<T, L: fmt::Display>(self) -> Report<T>
+    where
+        Self: Iterator<Item = (L, Report<T>)>,
+    {
+        aggregate(self, Location::caller())
+    }
+}
+
+// Returns the first success or gathers every failure into the causes of a single error
+fn aggregate<T, L: fmt::Display>(alternatives: impl Iterator<Item = (L, Report<T>)>, location: &'static Location<'static>) -> Report<T> {
+    let mut causes = Vec::new();
+    for (label, report) in alternatives {
+        match report {
+            Ok(val) => return Ok(val),
+            Err(e) => causes.push(Nuhound::new(label_of(&label)).caused_by(e)),
+        }
+    }
+    let inform = match causes.len() {
+        0 => "no alternatives were given".to_string(),
+        count => format!("all {} alternatives failed", count),
+    };
+    Err(causes.into_iter().fold(Nuhound::located(inform, location), Nuhound::with_cause))
+}
+
+// Names an alternative quoting any label that isn't a number
+fn label_of(label: &impl fmt::Display) -> String {
+    let label = label.to_string();
+    match label.parse::<usize>() {
+        Ok(_) => format!("alternative {} failed", label),
+        Err(_) => format!("alternative '{}' failed", label),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::here;
+
+    #[test]
+    fn test_01() {
+        let mut calls = Vec::new();
+        let mut attempt = |name: &'static str, result: Report<u32>| {
+            calls.push(name);
+            result
+        };
+        let value = first_ok([
+            ("env", Err(Nuhound::new("unset"))),
+            ("file", Ok(3)),
+            ("defaults", Ok(4)),
+        ].map(|(name, result)| (name, move || result)));
+        assert_eq!(value, Ok(3));
+        let value = [("one", Err(Nuhound::new("first"))), ("two", Ok(2)), ("three", Ok(5))]
+            .into_iter()
+            .map(|(name, result)| (name, attempt(name, result)))
+            .first_ok_labelled();
+        assert_eq!(value, Ok(2));
+        assert_eq!(calls, ["one", "two"]);
+    }
+
+    #[test]
+    fn test_02() {
+        let e = first_ok([
+            ("env", here!(Root, "unset")),
+            ("file", Nuhound::new("missing").caused_by(Nuhound::new("os error"))),
+        ].map(|(name, error)| (name, move || Err::<u8, _>(error)))).unwrap_err();
+        let values: Vec<String> = e.trace().split('\n').map(String::from).collect();
+        assert!(values[0].ends_with("all 2 alternatives failed"));
+        assert_eq!(values[1..3], ["    cause 1:", "     0: alternative 'env' failed"]);
+        assert!(values[3].starts_with("     1: ") && values[3].ends_with("unset"));
+        assert_eq!(values[4..], ["    cause 2:", "     0: alternative 'file' failed", "     1: missing", "     2: os error"]);
+
+        let empty: Vec<Report<u8>> = Vec::new();
+        assert!(empty.into_iter().first_ok().unwrap_err().to_string().ends_with("no alternatives were given"));
+        let nested = Nuhound::new("outer").with_cause(e);
+        assert!(nested.trace().contains("\n        cause 1:\n         0: alternative 'env' failed"));
+    }
+}
@@ -0,0 +1,267 @@
+use std::panic::Location;
+
+use crate::{Nuhound, Report};
+
+/// How [`ErrorBudget`] groups the failures that are not kept in full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupBy {
+    /// Failures with the same message, whatever their location, are counted together
+    #[default]
+    Message,
+    /// Failures raised at the same location are counted together
+    Location,
+}
+
+/// Tolerates failures in a long running loop, such as a batch import, until a limit is exceeded.
+/// The limit can be an absolute number of failures, a ratio of failures to items, or both. Each
+/// item's [`Report`] is passed to `track()`, which returns an error summarising every failure once
+/// the budget is exceeded.
+///
+/// The summary holds the first failures in full, 5 unless told otherwise, labelled with the index
+/// of their item. The remaining failures are counted by message or by location. A ratio is only
+/// checked by `track()` once 100 items have been seen, unless told otherwise, so that a failure
+/// among the first few items doesn't exceed it; `finish()` always checks it.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{ErrorBudget, ResultExtension};
+///
+/// let rows = ["1", "x", "3", "y", "z", "6"];
+/// let mut budget = ErrorBudget::new().with_max_failures(2).with_kept(1);
+/// let mut total = 0;
+/// for row in rows {
+///     match budget.track(row.parse::<u32>().easy()) {
+///         Ok(Some(value)) => total += value,
+///         Ok(None) => continue,
+///         Err(e) => {
+///             println!("{}", e.trace());
+///             break;
+///         },
+///     }
+/// }
+/// // This will emit:
+/// //  0: error budget exceeded: 3 of 5 items failed (limit: 2 failures)
+/// //     cause 1:
+/// //      0: item 1 failed
+/// //      1: invalid digit found in string
+/// //     cause 2:
+/// //      0: 2 more failures with message 'invalid digit found in string'
+/// assert_eq!(total, 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ErrorBudget {
+    max_failures: Option<usize>,
+    max_ratio: Option<f64>,
+    min_items: usize,
+    kept: usize,
+    group_by: GroupBy,
+    items: usize,
+    failures: Vec<Nuhound>,
+    groups: Vec<(String, usize)>,
+}
+
+impl Default for ErrorBudget {
+    fn default() -> Self {
+        ErrorBudget {
+            max_failures: None,
+            max_ratio: None,
+            min_items: 100,
+            kept: 5,
+            group_by: GroupBy::Message,
+            items: 0,
+            failures: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+}
+
+impl ErrorBudget {
+    /// Create a budget without limits.
+    pub fn new() -> Self {
+        ErrorBudget::default()
+    }
+
+    /// Exceed the budget when there are more than the given number of failures.
+    pub fn with_max_failures(mut self, max_failures: usize) -> Self {
+        self.max_failures = Some(max_failures);
+        self
+    }
+
+    /// Exceed the budget when the ratio of failures to items is more than the given ratio, such
+    /// as 0.01 for 1% of items.
+    pub fn with_max_ratio(mut self, max_ratio: f64) -> Self {
+        self.max_ratio = Some(max_ratio);
+        self
+    }
+
+    /// Set the number of items to see before `track()` checks the ratio.
+    pub fn with_min_items(mut self, min_items: usize) -> Self {
+        self.min_items = min_items;
+        self
+    }
+
+    /// Set the number of failures held in full by the summary.
+    pub fn with_kept(mut self, kept: usize) -> Self {
+        self.kept = kept;
+        self
+    }
+
+    /// Set how the failures that are not held in full are counted.
+    pub fn with_group_by(mut self, group_by: GroupBy) -> Self {
+        self.group_by = group_by;
+        self
+    }
+
+    /// Counts an item and returns its value. A failure within the budget gives `Ok(None)`. The
+    /// item that exceeds the budget, and every item after it, gives the summary.
+    #[track_caller]
+    pub fn track<T>(&mut self, report: Report<T>) -> Report<Option<T>> {
+        if self.is_exceeded(false) {
+            return Err(self.summary(Location::caller()));
+        }
+        self.items += 1;
+        let value = match report {
+            Ok(val) => Some(val),
+            Err(e) => {
+                self.record(e);
+                None
+            },
+        };
+        match self.is_exceeded(false) {
+            true => Err(self.summary(Location::caller())),
+            false => Ok(value),
+        }
+    }
+
+    /// Returns the number of items counted.
+    pub fn items(&self) -> usize {
+        self.items
+    }
+
+    /// Returns the number of failures counted.
+    pub fn failures(&self) -> usize {
+        self.failures.len() + self.groups.iter().map(|(_, count)| count).sum::<usize>()
+    }
+
+    /// Returns an error summarising every failure if the budget has been exceeded. The ratio is
+    /// checked however few items were counted.
+    #[track_caller]
+    pub fn finish(self) -> Report<()> {
+        match self.is_exceeded(true) {
+            true => Err(self.summary(Location::caller())),
+            false => Ok(()),
+        }
+    }
+
+    // Keeps a failure in full or counts it with those like it
+    fn record(&mut self, error: Nuhound) {
+        if self.failures.len() < self.kept {
+            let index = self.items - 1;
+            self.failures.push(Nuhound::new(format!("item {} failed", index)).caused_by(error));
+            return;
+        }
+        let key = match self.group_by {
+            GroupBy::Message => error.message.clone(),
+            GroupBy::Location => location_of(&error),
+        };
+        match self.groups.iter_mut().find(|(group, _)| *group == key) {
+            Some((_, count)) => *count += 1,
+            None => self.groups.push((key, 1)),
+        }
+    }
+
+    fn is_exceeded(&self, finished: bool) -> bool {
+        let failures = self.failures();
+        let too_many = self.max_failures.is_some_and(|max| failures > max);
+        let checked = finished || self.items >= self.min_items;
+        let too_often = checked && self.items > 0 && self.max_ratio.is_some_and(|max| failures as f64 / self.items as f64 > max);
+        too_many || too_often
+    }
+
+    // Summarises every failure in a single error
+    fn summary(&self, location: &'static Location<'static>) -> Nuhound {
+        let limits: Vec<String> = self.max_failures.map(|max| format!("{} failure{}", max, if max == 1 { "" } else { "s" }))
+            .into_iter()
+            .chain(self.max_ratio.map(|max| format!("{}% of items", percent(max))))
+            .collect();
+        let inform = format!("error budget exceeded: {} of {} items failed (limit: {})", self.failures(), self.items, limits.join(" or "));
+        let groups = self.groups.iter().map(|(key, count)| {
+            let more = format!("{} more failure{}", count, if *count == 1 { "" } else { "s" });
+            match self.group_by {
+                GroupBy::Message => Nuhound::new(format!("{} with message '{}'", more, key)),
+                GroupBy::Location => Nuhound::new(format!("{} at {}", more, key)),
+            }
+        });
+        self.failures.iter().cloned().chain(groups).fold(Nuhound::located(inform, location), Nuhound::with_cause)
+    }
+}
+
+// Formats a ratio as a percentage to at most four decimal places, without trailing zeros
+fn percent(ratio: f64) -> String {
+    let text = format!("{:.4}", ratio * 100.0);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// Returns the first location recorded by the chain of an error
+fn location_of(error: &Nuhound) -> String {
+    match error.chain().find_map(Nuhound::location) {
+        Some(location) => location.to_string(),
+        None => "an unknown location".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::here;
+
+    #[test]
+    fn test_01() {
+        let mut budget = ErrorBudget::new().with_max_ratio(0.25).with_min_items(4).with_kept(2);
+        let reports = [Ok(1), Err(here!(Root, "bad row")), Err(here!(Root, "bad row")), Ok(4), Err(Nuhound::new("empty row")), Ok(6)];
+        let tracked: Vec<Report<Option<u8>>> = reports.into_iter().map(|report| budget.track(report)).collect();
+        assert_eq!(tracked[..3], [Ok(Some(1)), Ok(None), Ok(None)]);
+        let e = tracked[3].as_ref().unwrap_err();
+        assert!(e.to_string().ends_with("error budget exceeded: 2 of 4 items failed (limit: 25% of items)"));
+        assert_eq!(tracked[4..], [Err(e.clone()), Err(e.clone())]);
+        assert_eq!((budget.items(), budget.failures()), (4, 2));
+
+        let mut budget = ErrorBudget::new().with_max_ratio(0.5);
+        assert_eq!(budget.track(Err::<u8, _>(Nuhound::new("first"))), Ok(None));
+        let e = budget.finish().unwrap_err();
+        assert!(e.to_string().ends_with("1 of 1 items failed (limit: 50% of items)"));
+        assert!(e.causes()[0].to_string().ends_with("item 0 failed"));
+        assert_eq!(ErrorBudget::new().with_max_failures(0).finish(), Ok(()));
+        assert_eq!([percent(0.07), percent(0.5), percent(0.123456), percent(1.0)], ["7", "50", "12.3456", "100"]);
+    }
+
+    #[test]
+    fn test_02() {
+        let mut budget = ErrorBudget::new().with_max_failures(3).with_max_ratio(0.9).with_kept(1);
+        let errors = [here!(Root, "duplicate key"), here!(Root, "duplicate key"), Nuhound::new("too long"), Nuhound::new("duplicate key")];
+        let mut tracked = errors.into_iter().map(|e| budget.track(Err::<u8, _>(e)));
+        assert!(tracked.by_ref().take(3).all(|report| report == Ok(None)));
+        let e = tracked.next().unwrap().unwrap_err();
+        let causes: Vec<String> = e.causes().iter().map(|cause| cause.message().to_string()).collect();
+        assert!(e.message().ends_with("4 of 4 items failed (limit: 3 failures or 90% of items)"));
+        assert_eq!(causes[1..], ["2 more failures with message 'duplicate key'", "1 more failure with message 'too long'"]);
+
+        let mut budget = ErrorBudget::new().with_max_failures(1).with_kept(0).with_group_by(GroupBy::Location);
+        let mut located = Nuhound::new("first");
+        located.location = Some(Location::caller());
+        let _ = budget.track(Err::<u8, _>(Nuhound::new("outer").caused_by(located)));
+        let e = budget.track(Err::<u8, _>(Nuhound::new("second"))).unwrap_err();
+        let causes: Vec<&str> = e.causes().iter().map(|cause| cause.message()).collect();
+        assert!(causes[0].starts_with("1 more failure at src/budget.rs:"));
+        assert_eq!(causes[1], "1 more failure at an unknown location");
+
+        // A message that happens to start like a location is grouped as it is
+        let mut budget = ErrorBudget::new().with_max_failures(1).with_kept(0);
+        let _ = budget.track(Err::<u8, _>(Nuhound::new("cfg.toml:3:5: bad key")));
+        let e = budget.track(Err::<u8, _>(Nuhound::new("cfg.toml:9:1: bad key"))).unwrap_err();
+        let causes: Vec<&str> = e.causes().iter().map(|cause| cause.message()).collect();
+        assert_eq!(causes, ["1 more failure with message 'cfg.toml:3:5: bad key'", "1 more failure with message 'cfg.toml:9:1: bad key'"]);
+        assert_eq!(location_of(&Nuhound::new("cfg.toml:3:5: bad key")), "an unknown location");
+    }
+}
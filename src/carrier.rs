@@ -0,0 +1,201 @@
+use std::error::Error;
+use std::fmt;
+use std::panic::Location;
+
+use crate::{Kind, Nuhound, Report};
+
+/// Provides `Nuhound` trait support to `bool` so that a condition can be turned into a
+/// [`Report<()>`] without an `if` statement.
+///
+/// When the disclose feature is enabled the location of the caller is recorded on the error,
+/// unless the error already discloses a location such as one created by the `here!` macro.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, here, BoolExtension};
+///
+/// fn check(items: &[u32]) -> Report<()> {
+///     items.is_empty().report_true(|| here!(Root, "No items were given"))?;
+///     items.contains(&42).report_false(|| here!(Root, "The answer is missing"))?;
+///     Ok(())
+/// }
+///
+/// let e = check(&[1, 2, 3]).unwrap_err();
+/// println!("{e}");
+/// // This will emit:
+/// // The answer is missing
+/// ```
+pub trait BoolExtension {
+    /// Returns `Ok(())` when the value is true, otherwise the error returned by `op`.
+    #[track_caller]
+    fn report_false<O: FnOnce() -> Nuhound>(self, op: O) -> Report<()>;
+
+    /// Returns `Ok(())` when the value is false, otherwise the error returned by `op`.
+    #[track_caller]
+    fn report_true<O: FnOnce() -> Nuhound>(self, op: O) -> Report<()>;
+}
+
+impl BoolExtension for bool {
+    #[track_caller]
+    fn report_false<O: FnOnce() -> Nuhound>(self, op: O) -> Report<()> {
+        match self {
+            true => Ok(()),
+            false => Err(op().locate(Location::caller())),
+        }
+    }
+
+    #[track_caller]
+    fn report_true<O: FnOnce() -> Nuhound>(self, op: O) -> Report<()> {
+        match self {
+            true => Err(op().locate(Location::caller())),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Provides `Nuhound` trait support to the results of lookups that may find nothing. These are
+/// `Result<Option<T>, E>`, such as a database query, and `Option<Result<T, E>>`, such as the
+/// `next()` item of an iterator over results.
+///
+/// A value that is found is returned as `Ok(T)`. A missing value becomes a Nuhound error of kind
+/// [`Kind::NotFound`] and any other error is converted in the same way as `easy()`. The error
+/// type `E` may be any type that implements [`std::error::Error`].
+///
+/// When the disclose feature is enabled the location of the caller is recorded on the error,
+/// unless the error already discloses a location such as one created by the `here!` macro.
+///
+/// # Example
+///
+/// ```
+/// use std::num::ParseIntError;
+/// use nuhound::{Kind, Report, FoundExtension};
+///
+/// fn lookup(key: &str) -> Result<Option<u32>, ParseIntError> {
+///     match key {
+///         "answer" => Ok(Some("42".parse::<u32>()?)),
+///         _ => Ok(None),
+///     }
+/// }
+///
+/// fn first(list: &[&str]) -> Report<u32> {
+///     let value = list.iter().map(|text| text.parse::<u32>()).next().found()?;
+///     Ok(value)
+/// }
+///
+/// assert_eq!(lookup("answer").found(), Ok(42));
+/// let e = lookup("question").found_or("No value for 'question'").unwrap_err();
+/// assert_eq!(e.kind(), Some(Kind::NotFound));
+/// println!("{e}");
+/// // This will emit:
+/// // No value for 'question'
+///
+/// let e = first(&[]).unwrap_err();
+/// println!("{e}");
+/// // This will emit:
+/// // Option::None detected
+/// ```
+pub trait FoundExtension<T, E> {
+    /// Transforms the lookup into a [`Result<T, Nuhound>`] reporting a missing value with the
+    /// message "Ok(None) detected" for a `Result` or "Option::None detected" for an `Option`.
+    #[track_caller]
+    fn found(self) -> Report<T>;
+
+    /// Transforms the lookup into a [`Result<T, Nuhound>`] reporting a missing value with the
+    /// given message.
+    #[track_caller]
+    fn found_or(self, inform: impl fmt::Display) -> Report<T>;
+}
+
+impl<T, E: Error> FoundExtension<T, E> for Result<Option<T>, E> {
+    #[track_caller]
+    fn found(self) -> Report<T> {
+        self.found_or("Ok(None) detected")
+    }
+
+    #[track_caller]
+    fn found_or(self, inform: impl fmt::Display) -> Report<T> {
+        match self {
+            Ok(Some(val)) => Ok(val),
+            Ok(None) => Err(Nuhound::located(inform, Location::caller()).with_kind(Kind::NotFound)),
+            Err(e) => Err(Nuhound::from_any_located(&e, Location::caller())),
+        }
+    }
+}
+
+impl<T, E: Error> FoundExtension<T, E> for Option<Result<T, E>> {
+    #[track_caller]
+    fn found(self) -> Report<T> {
+        self.found_or("Option::None detected")
+    }
+
+    #[track_caller]
+    fn found_or(self, inform: impl fmt::Display) -> Report<T> {
+        match self {
+            Some(Ok(val)) => Ok(val),
+            None => Err(Nuhound::located(inform, Location::caller()).with_kind(Kind::NotFound)),
+            Some(Err(e)) => Err(Nuhound::from_any_located(&e, Location::caller())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OptionExtension, here};
+    use regex::Regex;
+
+    #[test]
+    fn test_01() {
+        let trace = |report: Report<()>| report.unwrap_err().trace();
+        assert_eq!(true.report_false(|| here!(Root, "unused")), Ok(()));
+        assert_eq!(false.report_true(|| here!(Root, "unused")), Ok(()));
+        let values = [trace(false.report_false(|| Nuhound::new("was false"))), trace(true.report_true(|| here!(Root, "was true")))];
+        if cfg!(feature = "disclose") {
+            let re = Regex::new(r"^ 0: src[\\/]carrier\.rs:\d+:\d+: was (false|true)$").unwrap();
+            assert!(values.iter().all(|value| re.is_match(value)));
+        } else {
+            assert_eq!(values, [" 0: was false", " 0: was true"]);
+        }
+    }
+
+    #[test]
+    fn test_02() {
+        let found: Result<Option<u8>, std::num::ParseIntError> = Ok(None);
+        let e = found.found().unwrap_err();
+        assert_eq!(e.kind(), Some(Kind::NotFound));
+        assert!(e.to_string().ends_with("Ok(None) detected"));
+        let failed: Result<Option<u8>, std::io::Error> = Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "too slow"));
+        let e = failed.found_or("not used").unwrap_err();
+        assert_eq!(e.kind(), Some(Kind::TimedOut));
+        assert!(e.to_string().ends_with("too slow"));
+        assert_eq!(Ok::<_, std::io::Error>(Some(3)).found(), Ok(3));
+
+        let mut items = ["1", "x"].iter().map(|text| text.parse::<u8>());
+        assert_eq!(items.next().found(), Ok(1));
+        assert!(items.next().found().unwrap_err().trace().ends_with("invalid digit found in string"));
+        let e = items.next().found_or("no third item").unwrap_err();
+        assert_eq!(e.kind(), Some(Kind::NotFound));
+        assert!(e.trace().ends_with("no third item (kind: NotFound)"));
+
+        let e = [1, 2].get(2).easy_none("no third value").unwrap_err();
+        if cfg!(feature = "disclose") {
+            let re = Regex::new(r"^ 0: src[\\/]carrier\.rs:\d+:\d+: no third value$").unwrap();
+            assert!(re.is_match(&e.trace()));
+        } else {
+            assert_eq!(e.trace(), " 0: no third value");
+        }
+
+        // A type implementing only the required methods gets easy_none() from the trait
+        struct Missing;
+        impl OptionExtension<u8> for Missing {
+            fn report<O: FnOnce(Nuhound) -> Nuhound>(self, op: O) -> Report<u8> {
+                Err(op(Nuhound::new("missing")))
+            }
+            fn easy(self) -> Report<u8> {
+                Err(Nuhound::new("missing"))
+            }
+        }
+        assert!(Missing.easy_none("no value").unwrap_err().to_string().ends_with("no value"));
+    }
+}
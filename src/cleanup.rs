@@ -0,0 +1,97 @@
+use crate::Report;
+
+/// Runs the operation and then always runs the cleanup. If both fail, the error of the operation
+/// is returned with the error of the cleanup attached as suppressed so that neither is lost. If
+/// only the cleanup fails, its error is returned. If the operation panics, the cleanup is still
+/// run as the panic unwinds and any error it returns is discarded.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, here, with_cleanup};
+///
+/// fn save() -> Report<()> {
+///     Err(here!(Root, "Disk full"))
+/// }
+///
+/// fn remove_temporary_file() -> Report<()> {
+///     Err(here!(Root, "Permission denied"))
+/// }
+///
+/// let e = with_cleanup(save, remove_temporary_file).unwrap_err();
+/// assert_eq!(e.suppressed().len(), 1);
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: Disk full
+/// //     suppressed 1:
+/// //      0: Permission denied
+/// ```
+pub fn with_cleanup<T, O, C>(op: O, cleanup: C) -> Report<T>
+where
+    O: FnOnce() -> Report<T>,
+    C: FnOnce() -> Report<()>,
+{
+    let mut guard = Guard(Some(cleanup));
+    let result = op();
+    let cleanup = guard.0.take().expect("the cleanup has not run");
+    match (result, cleanup()) {
+        (Err(e), Err(suppressed)) => Err(e.with_suppressed(suppressed)),
+        (Ok(_), Err(e)) => Err(e),
+        (result, Ok(())) => result,
+    }
+}
+
+// Runs the cleanup when dropped, unless it has been taken to be run in the normal way
+struct Guard<C: FnOnce() -> Report<()>>(Option<C>);
+
+impl<C: FnOnce() -> Report<()>> Drop for Guard<C> {
+    fn drop(&mut self) {
+        if let Some(cleanup) = self.0.take() {
+            let _ = cleanup();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Nuhound;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_01() {
+        let cleaned = Cell::new(0);
+        let cleanup = |result: Report<()>| { cleaned.set(cleaned.get() + 1); result };
+        assert_eq!(with_cleanup(|| Ok(1), || cleanup(Ok(()))), Ok(1));
+        let e = with_cleanup(|| Err::<u8, _>(Nuhound::new("failed")), || cleanup(Ok(()))).unwrap_err();
+        assert_eq!(e, Nuhound::new("failed"));
+        let e = with_cleanup(|| Ok(1), || cleanup(Err(Nuhound::new("flush failed")))).unwrap_err();
+        assert_eq!(e, Nuhound::new("flush failed"));
+        assert_eq!(cleaned.get(), 3);
+
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            with_cleanup(|| -> Report<u8> { panic!("operation panicked") }, || cleanup(Err(Nuhound::new("discarded"))))
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(cleaned.get(), 4);
+    }
+
+    #[test]
+    fn test_02() {
+        let operation = || Err::<u8, _>(Nuhound::new("write failed").caused_by(Nuhound::new("disk full")));
+        let rollback = || Err(Nuhound::new("rollback failed").with_suppressed(Nuhound::new("lock lost")));
+        let e = with_cleanup(operation, rollback).unwrap_err();
+        let e = Nuhound::new("saving").caused_by(e).with_suppressed(Nuhound::new("log closed"));
+        assert_eq!(e.trace(), [
+            " 0: saving",
+            " 1: write failed",
+            " 2: disk full",
+            "    suppressed 1:",
+            "     0: log closed",
+            "    suppressed 2:",
+            "     0: rollback failed",
+            "        suppressed 1:",
+            "         0: lock lost",
+        ].join("\n"));
+    }
+}
@@ -0,0 +1,229 @@
+use std::error::Error;
+use std::fmt;
+use std::panic::Location;
+
+use crate::{Nuhound, Report, __private};
+
+/// Adds a message to the error of a `Result` or to the `None` of an `Option` producing a
+/// [`Report<T>`].
+///
+/// The error is wrapped in the same way as `here!(e, "message")` so the underlying chain is
+/// retained and can be displayed using the `trace` method. When the `disclose` feature is enabled
+/// the new link records the location of the caller, which is returned by `location()` and shown
+/// before the message by `trace()`.
+///
+/// The error type may be any type that implements [`std::error::Error`] as well as
+/// `Box<dyn Error>`, `Box<dyn Error + Send + Sync>`, `String` and `&str`. The `M` parameter is
+/// used internally to distinguish these error types and never needs to be specified.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, Context};
+///
+/// fn load(path: &str) -> Report<u32> {
+///     let text = std::fs::read_to_string(path).context("Unable to read the configuration")?;
+///     let value = text.trim().parse::<u32>()
+///         .with_context(|| format!("'{path}' does not contain an integer"))?;
+///     Ok(value)
+/// }
+///
+/// let e = load("xuhgd56qhsl").unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: Unable to read the configuration
+/// //  1: No such file or directory (os error 2)
+/// ```
+pub trait Context<T, E, M = ()> {
+    /// Wraps any error with the given message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::Context;
+    ///
+    /// let list = vec![1, 2, 3, 4];
+    /// let e = list.get(4).context("Index out of bounds").unwrap_err();
+    /// println!("{}", e.trace());
+    /// // This will emit:
+    /// //  0: Index out of bounds
+    /// //  1: Option::None detected
+    /// ```
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> Report<T>;
+
+    /// Wraps any error with the message returned by `op`. The message is only created if there is
+    /// an error to be wrapped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::Context;
+    ///
+    /// let text = "NaN";
+    /// let e = text.parse::<u32>()
+    ///     .with_context(|| format!("'{text}' is not an integer"))
+    ///     .unwrap_err();
+    /// println!("{}", e.trace());
+    /// // This will emit:
+    /// //  0: 'NaN' is not an integer
+    /// //  1: invalid digit found in string
+    /// ```
+    #[track_caller]
+    fn with_context<C: fmt::Display, O: FnOnce() -> C>(self, op: O) -> Report<T>;
+}
+
+impl<T, E: Error> Context<T, E> for Result<T, E> {
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> Report<T> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::located(context, Location::caller()).caused_by(Nuhound::from_any(&e))),
+        }
+    }
+
+    #[track_caller]
+    fn with_context<C: fmt::Display, O: FnOnce() -> C>(self, op: O) -> Report<T> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::located(op(), Location::caller()).caused_by(Nuhound::from_any(&e))),
+        }
+    }
+}
+
+impl<T> Context<T, Box<dyn Error>, __private::Boxed> for Result<T, Box<dyn Error>> {
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> Report<T> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::located(context, Location::caller()).caused_by(Nuhound::from_source(e.as_ref()))),
+        }
+    }
+
+    #[track_caller]
+    fn with_context<C: fmt::Display, O: FnOnce() -> C>(self, op: O) -> Report<T> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::located(op(), Location::caller()).caused_by(Nuhound::from_source(e.as_ref()))),
+        }
+    }
+}
+
+impl<T> Context<T, Box<dyn Error + Send + Sync>, __private::Boxed> for Result<T, Box<dyn Error + Send + Sync>> {
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> Report<T> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::located(context, Location::caller()).caused_by(Nuhound::from_source(e.as_ref()))),
+        }
+    }
+
+    #[track_caller]
+    fn with_context<C: fmt::Display, O: FnOnce() -> C>(self, op: O) -> Report<T> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::located(op(), Location::caller()).caused_by(Nuhound::from_source(e.as_ref()))),
+        }
+    }
+}
+
+impl<T> Context<T, String, __private::Text> for Result<T, String> {
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> Report<T> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::located(context, Location::caller()).caused_by(Nuhound::new(e))),
+        }
+    }
+
+    #[track_caller]
+    fn with_context<C: fmt::Display, O: FnOnce() -> C>(self, op: O) -> Report<T> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::located(op(), Location::caller()).caused_by(Nuhound::new(e))),
+        }
+    }
+}
+
+impl<'a, T> Context<T, &'a str, __private::Text> for Result<T, &'a str> {
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> Report<T> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::located(context, Location::caller()).caused_by(Nuhound::new(e))),
+        }
+    }
+
+    #[track_caller]
+    fn with_context<C: fmt::Display, O: FnOnce() -> C>(self, op: O) -> Report<T> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::located(op(), Location::caller()).caused_by(Nuhound::new(e))),
+        }
+    }
+}
+
+impl<T> Context<T, ()> for Option<T> {
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> Report<T> {
+        match self {
+            Some(val) => Ok(val),
+            None => Err(Nuhound::located(context, Location::caller()).caused_by(Nuhound::new("Option::None detected"))),
+        }
+    }
+
+    #[track_caller]
+    fn with_context<C: fmt::Display, O: FnOnce() -> C>(self, op: O) -> Report<T> {
+        match self {
+            Some(val) => Ok(val),
+            None => Err(Nuhound::located(op(), Location::caller()).caused_by(Nuhound::new("Option::None detected"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kind, here};
+    use regex::Regex;
+
+    #[test]
+    fn test_01() {
+        fn bad_value() -> Report<u32> {
+            let value = "NaN".parse::<u32>()
+                .context("cannot convert string to a number")?;
+            Ok(value)
+        }
+        let value = bad_value().unwrap_err().trace();
+        let values: Vec<&str> = value.split('\n').collect();
+        if cfg!(feature = "disclose") {
+            let re0 = Regex::new(r"^ 0: src[\\/]context\.rs:\d+:\d+: cannot convert string to a number$").unwrap();
+            assert!(re0.is_match(values[0]));
+        } else {
+            assert_eq!(values[0], " 0: cannot convert string to a number");
+        }
+        assert_eq!(values[1], " 1: invalid digit found in string");
+    }
+
+    #[test]
+    fn test_02() {
+        fn layer2() -> Report<()> {
+            Err(here!(Root, "Root cause").with_kind(Kind::NotFound))
+        }
+        let mut called = false;
+        let e = layer2().with_context(|| {
+            called = true;
+            "Layer 1 failure"
+        }).unwrap_err();
+        assert!(called && e.to_string().ends_with("Layer 1 failure"));
+        let source = e.source().unwrap().downcast_ref::<Nuhound>().unwrap();
+        assert_eq!(source.kind(), Some(Kind::NotFound));
+        let mut called = false;
+        let value = Some(42).with_context(|| {
+            called = true;
+            "Not called"
+        });
+        assert_eq!(value, Ok(42));
+        assert!(!called);
+    }
+}
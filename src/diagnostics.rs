@@ -0,0 +1,292 @@
+use std::fmt;
+use std::panic::Location;
+
+use crate::{Nuhound, Report};
+
+/// The severity of a problem recorded by [`Diagnostics`]. Severities are ordered from `Note`, the
+/// least severe, to `Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Note => write!(f, "note"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem recorded by [`Diagnostics`] together with its severity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    severity: Severity,
+    error: Nuhound,
+}
+
+impl Diagnostic {
+    /// Returns the severity of the problem
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the error describing the problem
+    pub fn error(&self) -> &Nuhound {
+        &self.error
+    }
+
+    // Labels the error with its severity
+    fn labelled(&self) -> Nuhound {
+        Nuhound::new(self.severity).caused_by(self.error.clone())
+    }
+}
+
+/// Collects problems that don't stop processing, such as the recoverable errors met by a parser,
+/// so that they can all be reported at the end.
+///
+/// Each problem is a Nuhound error recorded with a [`Severity`]. Processing fails once a problem
+/// at or above the threshold has been recorded; the threshold is `Severity::Error` unless changed
+/// with `with_threshold()`. The `finish()` method returns the value together with the problems
+/// below the threshold, or a single Nuhound error whose causes hold every problem.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Diagnostics, Nuhound, Report, ResultExtension};
+///
+/// fn parse(lines: &[&str]) -> Report<(Vec<u32>, Vec<nuhound::Diagnostic>)> {
+///     let mut diagnostics = Diagnostics::new();
+///     let mut values = Vec::new();
+///     for line in lines {
+///         if line.is_empty() {
+///             diagnostics.warning(Nuhound::new("Empty line ignored"));
+///             continue;
+///         }
+///         if let Some(value) = diagnostics.extend_from(line.parse::<u32>().easy()) {
+///             values.push(value);
+///         }
+///     }
+///     diagnostics.finish(values)
+/// }
+///
+/// let (values, warnings) = parse(&["1", "", "3"]).unwrap();
+/// assert_eq!((values, warnings.len()), (vec![1, 3], 1));
+///
+/// let e = parse(&["1", "", "x"]).unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: 1 error and 1 warning reported
+/// //     cause 1:
+/// //      0: warning
+/// //      1: Empty line ignored
+/// //     cause 2:
+/// //      0: error
+/// //      1: invalid digit found in string
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+    threshold: Severity,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Diagnostics::new()
+    }
+}
+
+impl Diagnostics {
+    /// Create an empty collector that fails on errors
+    pub fn new() -> Self {
+        Diagnostics {
+            diagnostics: Vec::new(),
+            threshold: Severity::Error,
+        }
+    }
+
+    /// Set the least severe problem that causes processing to fail. For example
+    /// `Severity::Warning` fails if there are any warnings or errors.
+    pub fn with_threshold(mut self, threshold: Severity) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Record a problem with the given severity
+    pub fn push(&mut self, severity: Severity, error: Nuhound) {
+        self.diagnostics.push(Diagnostic { severity, error });
+    }
+
+    /// Record an error
+    pub fn error(&mut self, error: Nuhound) {
+        self.push(Severity::Error, error);
+    }
+
+    /// Record a warning
+    pub fn warning(&mut self, error: Nuhound) {
+        self.push(Severity::Warning, error);
+    }
+
+    /// Record a note
+    pub fn note(&mut self, error: Nuhound) {
+        self.push(Severity::Note, error);
+    }
+
+    /// Returns the value of a successful report or records its error, returning `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Diagnostics, ResultExtension};
+    ///
+    /// let mut diagnostics = Diagnostics::new();
+    /// assert_eq!(diagnostics.extend_from("42".parse::<u32>().easy()), Some(42));
+    /// assert_eq!(diagnostics.extend_from("NaN".parse::<u32>().easy()), None);
+    /// assert!(diagnostics.has_failed());
+    /// ```
+    pub fn extend_from<T>(&mut self, report: Report<T>) -> Option<T> {
+        self.extend_from_as(Severity::Error, report)
+    }
+
+    /// Returns the value of a successful report or records its error with the given severity,
+    /// returning `None`.
+    pub fn extend_from_as<T>(&mut self, severity: Severity, report: Report<T>) -> Option<T> {
+        match report {
+            Ok(val) => Some(val),
+            Err(e) => {
+                self.push(severity, e);
+                None
+            },
+        }
+    }
+
+    /// Returns the problems in the order they were recorded
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns the number of problems with the given severity
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics.iter().filter(|diagnostic| diagnostic.severity == severity).count()
+    }
+
+    /// Determines whether any problem has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Determines whether a problem at or above the threshold has been recorded
+    pub fn has_failed(&self) -> bool {
+        self.diagnostics.iter().any(|diagnostic| diagnostic.severity >= self.threshold)
+    }
+
+    /// Returns an error holding every problem if a problem at or above the threshold has been
+    /// recorded, so that processing can stop early with `?`. The problems are kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Diagnostics, Nuhound, Severity};
+    ///
+    /// let mut diagnostics = Diagnostics::new().with_threshold(Severity::Warning);
+    /// diagnostics.note(Nuhound::new("Using defaults"));
+    /// assert!(diagnostics.check().is_ok());
+    /// diagnostics.warning(Nuhound::new("Deprecated setting"));
+    /// assert!(diagnostics.check().is_err());
+    /// ```
+    #[track_caller]
+    pub fn check(&self) -> Report<()> {
+        match self.has_failed() {
+            true => Err(self.aggregate(Location::caller())),
+            false => Ok(()),
+        }
+    }
+
+    /// Returns the value together with the problems below the threshold, or an error holding
+    /// every problem if a problem at or above the threshold has been recorded.
+    #[track_caller]
+    pub fn finish<T>(self, value: T) -> Report<(T, Vec<Diagnostic>)> {
+        match self.has_failed() {
+            true => Err(self.aggregate(Location::caller())),
+            false => Ok((value, self.diagnostics)),
+        }
+    }
+
+    /// Lists the problems in the same style as `Nuhound::trace()`, each headed by its severity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Diagnostics, Nuhound};
+    ///
+    /// let mut diagnostics = Diagnostics::new();
+    /// diagnostics.warning(Nuhound::new("Empty line ignored"));
+    /// diagnostics.error(Nuhound::new("Bad value").caused_by(Nuhound::new("invalid digit found in string")));
+    /// assert_eq!(diagnostics.trace(), "warning:\n     0: Empty line ignored\nerror:\n     0: Bad value\n     1: invalid digit found in string");
+    /// ```
+    pub fn trace(&self) -> String {
+        let mut trace_list = Vec::new();
+        for diagnostic in &self.diagnostics {
+            trace_list.push(format!("{}:", diagnostic.severity));
+            diagnostic.error.trace_into("    ", &mut trace_list, false);
+        }
+        trace_list.join("\n")
+    }
+
+    // Creates an error whose causes hold every problem
+    fn aggregate(&self, location: &'static Location<'static>) -> Nuhound {
+        let counts: Vec<String> = [Severity::Error, Severity::Warning, Severity::Note].iter()
+            .map(|severity| (self.count(*severity), severity))
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, severity)| format!("{} {}{}", count, severity, if count == 1 { "" } else { "s" }))
+            .collect();
+        let inform = match counts.split_last() {
+            Some((last, [])) => format!("{} reported", last),
+            Some((last, rest)) => format!("{} and {} reported", rest.join(", "), last),
+            None => "no problems reported".to_string(),
+        };
+        self.diagnostics.iter().map(Diagnostic::labelled).fold(Nuhound::located(inform, location), Nuhound::with_cause)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_01() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.note(Nuhound::new("n1"));
+        diagnostics.warning(Nuhound::new("w1"));
+        diagnostics.warning(Nuhound::new("w2"));
+        assert!(!diagnostics.has_failed());
+        assert_eq!(diagnostics.check(), Ok(()));
+        assert_eq!(diagnostics.extend_from_as(Severity::Note, Ok(5)), Some(5));
+        let (value, rest) = diagnostics.clone().finish("done").unwrap();
+        assert_eq!((value, rest.len(), rest[1].severity()), ("done", 3, Severity::Warning));
+
+        diagnostics.extend_from::<u8>(Err(Nuhound::new("e1")));
+        diagnostics.extend_from::<u8>(Err(Nuhound::new("e2")));
+        assert_eq!((diagnostics.count(Severity::Error), diagnostics.count(Severity::Warning)), (2, 2));
+        let e = diagnostics.finish(()).unwrap_err();
+        assert!(e.message().ends_with("2 errors, 2 warnings and 1 note reported"));
+        let labels: Vec<String> = e.causes().iter().map(|cause| format!("{}/{}", cause.message(), cause.chain().nth(1).unwrap().message())).collect();
+        assert_eq!(labels, ["note/n1", "warning/w1", "warning/w2", "error/e1", "error/e2"]);
+    }
+
+    #[test]
+    fn test_02() {
+        let mut diagnostics = Diagnostics::default().with_threshold(Severity::Note);
+        assert_eq!(diagnostics.trace(), "");
+        assert!(diagnostics.is_empty());
+        diagnostics.note(Nuhound::new("only a note"));
+        let e = diagnostics.check().unwrap_err();
+        assert!(e.message().ends_with("1 note reported"));
+        assert_eq!(e.trace().split_once('\n').unwrap().1, "    cause 1:\n     0: note\n     1: only a note");
+        assert_eq!(diagnostics.trace(), "note:\n     0: only a note");
+    }
+}
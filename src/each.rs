@@ -0,0 +1,252 @@
+use std::error::Error;
+use std::fmt;
+use std::panic::Location;
+
+use crate::{Nuhound, Report};
+
+/// Provides adapters to any iterator of `Result<T, E>` that record which item failed. Items are
+/// numbered from 0 in the order the iterator produces them.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::EachExtension;
+///
+/// let lines = ["8080", "http", "443", ""];
+/// let e = lines.iter().map(|line| line.parse::<u16>()).collect_all().unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: 2 of 4 items failed
+/// //     cause 1:
+/// //      0: item 1 failed
+/// //      1: invalid digit found in string
+/// //     cause 2:
+/// //      0: item 3 failed
+/// //      1: cannot parse integer from empty string
+///
+/// let ports = lines[..1].iter().map(|line| line.parse::<u16>()).collect_all();
+/// assert_eq!(ports, Ok(vec![8080]));
+/// ```
+pub trait EachExtension: Iterator + Sized {
+    /// Wraps each error in a Nuhound error whose message is produced by the closure from the
+    /// index of the item and the error. Successful items are passed through unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::EachExtension;
+    ///
+    /// let reports: Vec<_> = ["1", "two"].iter()
+    ///     .map(|text| text.parse::<u8>())
+    ///     .report_each(|index, _| format!("line {} is not a number", index + 1))
+    ///     .collect();
+    /// assert_eq!(reports[0], Ok(1));
+    /// println!("{}", reports[1].as_ref().unwrap_err().trace());
+    /// // This will emit:
+    /// //  0: line 2 is not a number
+    /// //  1: invalid digit found in string
+    /// ```
+    #[track_caller]
+    fn report_each<T, E, D, F>(self, op: F) -> ReportEach<Self, F>
+    where
+        Self: Iterator<Item = Result<T, E>>,
+        E: Error,
+        D: fmt::Display,
+        F: FnMut(usize, &E) -> D;
+
+    /// Consumes the iterator and returns every successful value, or an error whose causes hold
+    /// every failure labelled with the index of its item.
+    #[track_caller]
+    fn collect_all<T, E>(self) -> Report<Vec<T>>
+    where
+        Self: Iterator<Item = Result<T, E>>,
+        E: Error;
+
+    /// Consumes the iterator and separates the successful values from the failures. Each failure
+    /// is labelled with the index of its item.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::EachExtension;
+    ///
+    /// let (ports, errors) = ["80", "-1", "443"].iter().map(|text| text.parse::<u16>()).partition_reports();
+    /// assert_eq!(ports, [80, 443]);
+    /// assert_eq!(errors.len(), 1);
+    /// assert!(errors[0].to_string().ends_with("item 1 failed"));
+    /// ```
+    #[track_caller]
+    fn partition_reports<T, E>(self) -> (Vec<T>, Vec<Nuhound>)
+    where
+        Self: Iterator<Item = Result<T, E>>,
+        E: Error;
+
+    /// Consumes the iterator until the first failure and returns it labelled with the index of its
+    /// item. The items after the failure are never produced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::EachExtension;
+    ///
+    /// let e = ["1", "x", "y"].iter().map(|text| text.parse::<u8>()).collect_fail_fast().unwrap_err();
+    /// println!("{}", e.trace());
+    /// // This will emit:
+    /// //  0: item 1 failed
+    /// //  1: invalid digit found in string
+    /// ```
+    #[track_caller]
+    fn collect_fail_fast<T, E>(self) -> Report<Vec<T>>
+    where
+        Self: Iterator<Item = Result<T, E>>,
+        E: Error;
+}
+
+impl<I: Iterator> EachExtension for I {
+    #[track_caller]
+    fn report_each<T, E, D, F>(self, op: F) -> ReportEach<Self, F>
+    where
+        Self: Iterator<Item = Result<T, E>>,
+        E: Error,
+        D: fmt::Display,
+        F: FnMut(usize, &E) -> D,
+    {
+        ReportEach { iter: self, op, index: 0, location: Location::caller() }
+    }
+
+    #[track_caller]
+    fn collect_all<T, E>(self) -> Report<Vec<T>>
+    where
+        Self: Iterator<Item = Result<T, E>>,
+        E: Error,
+    {
+        let location = Location::caller();
+        let mut count = 0;
+        let (values, causes) = partition(self.inspect(|_| count += 1), location);
+        match causes.is_empty() {
+            true => Ok(values),
+            false => {
+                let inform = format!("{} of {} items failed", causes.len(), count);
+                Err(causes.into_iter().fold(Nuhound::located(inform, location), Nuhound::with_cause))
+            },
+        }
+    }
+
+    #[track_caller]
+    fn partition_reports<T, E>(self) -> (Vec<T>, Vec<Nuhound>)
+    where
+        Self: Iterator<Item = Result<T, E>>,
+        E: Error,
+    {
+        partition(self, Location::caller())
+    }
+
+    #[track_caller]
+    fn collect_fail_fast<T, E>(self) -> Report<Vec<T>>
+    where
+        Self: Iterator<Item = Result<T, E>>,
+        E: Error,
+    {
+        let location = Location::caller();
+        self.enumerate()
+            .map(|(index, result)| result.map_err(|e| failed(index, &e, location)))
+            .collect()
+    }
+}
+
+/// An iterator that wraps each error of the underlying iterator in a Nuhound error. It is created
+/// by [`EachExtension::report_each`].
+pub struct ReportEach<I, F> {
+    iter: I,
+    op: F,
+    index: usize,
+    location: &'static Location<'static>,
+}
+
+impl<T, E, D, I, F> Iterator for ReportEach<I, F>
+where
+    I: Iterator<Item = Result<T, E>>,
+    E: Error,
+    D: fmt::Display,
+    F: FnMut(usize, &E) -> D,
+{
+    type Item = Report<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.iter.next()?;
+        let index = self.index;
+        self.index += 1;
+        Some(result.map_err(|e| Nuhound::located((self.op)(index, &e), self.location).caused_by(Nuhound::from_any(&e))))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+// Separates the successful values from the failures labelled with the index of their item
+fn partition<T, E: Error>(iter: impl Iterator<Item = Result<T, E>>, location: &'static Location<'static>) -> (Vec<T>, Vec<Nuhound>) {
+    let mut values = Vec::new();
+    let mut failures = Vec::new();
+    for (index, result) in iter.enumerate() {
+        match result {
+            Ok(val) => values.push(val),
+            Err(e) => failures.push(failed(index, &e, location)),
+        }
+    }
+    (values, failures)
+}
+
+// Labels the failure of an item with its index
+fn failed<E: Error>(index: usize, error: &E, location: &'static Location<'static>) -> Nuhound {
+    Nuhound::located(format!("item {} failed", index), location).caused_by(Nuhound::from_any(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_01() {
+        let items = vec![Ok(1), Err(io::Error::new(io::ErrorKind::NotFound, "gone")), Ok(3), Err(io::Error::other("broken"))];
+        let reports: Vec<Report<u8>> = items.into_iter().report_each(|index, e| format!("item {index} ({}) rejected", e.kind())).collect();
+        assert_eq!(reports[0], Ok(1));
+        let e = reports[1].as_ref().unwrap_err();
+        assert!(e.to_string().ends_with("item 1 (entity not found) rejected"));
+        assert_eq!(e.chain().nth(1).unwrap().kind(), Some(crate::Kind::NotFound));
+        assert!(reports[3].as_ref().unwrap_err().to_string().ends_with("item 3 (other error) rejected"));
+
+        let mut produced = 0;
+        let e = (0..10).map(|n| {
+            produced += 1;
+            match n {
+                4 => Err(Nuhound::new("four").caused_by(Nuhound::new("unlucky"))),
+                _ => Ok(n),
+            }
+        }).collect_fail_fast().unwrap_err();
+        assert_eq!(produced, 5);
+        let values: Vec<&str> = e.chain().map(|link| link.message()).collect();
+        assert!(values[0].ends_with("item 4 failed"));
+        assert_eq!(values[1..], ["four", "unlucky"]);
+        assert_eq!([Ok::<_, Nuhound>(1), Ok(2)].into_iter().collect_fail_fast(), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_02() {
+        let parsed = ["1", "a", "3", "b", "5"].iter().map(|text| text.parse::<u8>());
+        let e = parsed.clone().collect_all().unwrap_err();
+        assert!(e.to_string().ends_with("2 of 5 items failed"));
+        let causes: Vec<String> = e.causes().iter().map(|cause| cause.to_string()).collect();
+        assert!(causes[0].ends_with("item 1 failed") && causes[1].ends_with("item 3 failed"));
+        assert_eq!(e.causes()[1].chain().nth(1).unwrap().message(), "invalid digit found in string");
+
+        let (values, errors) = parsed.partition_reports();
+        assert_eq!(values, [1, 3, 5]);
+        assert_eq!(errors.len(), 2);
+        let failures: Vec<&str> = errors.iter().map(|error| error.message()).collect();
+        assert_eq!(failures, ["item 1 failed", "item 3 failed"]);
+        let empty: Vec<Result<u8, Nuhound>> = Vec::new();
+        assert_eq!(empty.into_iter().collect_all(), Ok(Vec::new()));
+    }
+}
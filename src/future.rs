@@ -0,0 +1,354 @@
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::panic::Location;
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
+
+use crate::{Nuhound, Report, ScopeGuard, __private};
+
+/// Provides `report()`, `easy()` and `context()` to any future whose output is supported by
+/// `ResultExtension`, `OptionExtension` or `Context`. The methods convert the output of the future
+/// when it completes, so `fut.easy().await` is the same as `fut.await.easy()`.
+///
+/// When the disclose feature is enabled the location of the call to the method is recorded on the
+/// error.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, FutureExtension, here};
+///
+/// async fn fetch(id: u32) -> Result<String, std::io::Error> {
+///     Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("no record {id}")))
+/// }
+///
+/// async fn load(id: u32) -> Report<String> {
+///     let name = fetch(id).report(|e| here!(e, "fetch failed")).await?;
+///     Ok(name)
+/// }
+///
+/// async fn count() -> Report<usize> {
+///     fetch(7).context("counting records").await.map(|text| text.len())
+/// }
+///
+/// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+/// #     let mut future = std::pin::pin!(future);
+/// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+/// #     loop {
+/// #         if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+/// #             return output;
+/// #         }
+/// #     }
+/// # }
+/// let e = block_on(count()).unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: counting records
+/// //  1: no record 7
+/// assert!(block_on(load(7)).is_err());
+/// ```
+pub trait FutureExtension<T, E, M = ()>: Future + Sized {
+    /// Converts an error output with the closure.
+    #[track_caller]
+    fn report<O: FnOnce(E) -> Nuhound>(self, op: O) -> Reported<Self, __private::Reporter<O, (T, E, M)>>;
+
+    /// Converts an error output into a Nuhound error.
+    #[track_caller]
+    fn easy(self) -> Reported<Self, __private::Easy<(T, E, M)>>;
+
+    /// Converts an error output into a Nuhound error caused by the original error with the given
+    /// context as its message.
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> Reported<Self, __private::Contextual<C, (T, E, M)>>;
+}
+
+impl<F, T, E, M> FutureExtension<T, E, M> for F
+where
+    F: Future,
+    F::Output: __private::Outcome<T, E, M>,
+{
+    #[track_caller]
+    fn report<O: FnOnce(E) -> Nuhound>(self, op: O) -> Reported<Self, __private::Reporter<O, (T, E, M)>> {
+        Reported::new(self, __private::Reporter(op, PhantomData), None)
+    }
+
+    #[track_caller]
+    fn easy(self) -> Reported<Self, __private::Easy<(T, E, M)>> {
+        Reported::new(self, __private::Easy(PhantomData), None)
+    }
+
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> Reported<Self, __private::Contextual<C, (T, E, M)>> {
+        Reported::new(self, __private::Contextual(context, PhantomData), None)
+    }
+}
+
+/// Runs a future inside a scope. The description is visible to every Nuhound error created while
+/// the future is being polled, as if the poll ran inside [`scope!`](crate::scope!), and becomes
+/// the context of any error the future returns. Because the scope is entered on each poll it
+/// follows the future from thread to thread on a multi-threaded executor.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, here, in_scope};
+///
+/// async fn migrate(table: &str) -> Report<()> {
+///     Err(here!(Root, "disk full while migrating {table}"))
+/// }
+///
+/// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+/// #     let mut future = std::pin::pin!(future);
+/// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+/// #     loop {
+/// #         if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+/// #             return output;
+/// #         }
+/// #     }
+/// # }
+/// let e = block_on(in_scope("handling request 42", migrate("users"))).unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: handling request 42
+/// //  1: disk full while migrating users
+/// //     in handling request 42
+/// ```
+#[track_caller]
+pub fn in_scope<F, T, E, M>(description: impl fmt::Display, future: F) -> Reported<F, __private::Contextual<String, (T, E, M)>>
+where
+    F: Future,
+    F::Output: __private::Outcome<T, E, M>,
+{
+    let description = description.to_string();
+    Reported::new(future, __private::Contextual(description.clone(), PhantomData), Some(description))
+}
+
+/// A future that converts the output of another future into a [`Report`]. It is created by the
+/// methods of [`FutureExtension`] and by [`in_scope`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Reported<F, A> {
+    future: F,
+    action: Option<A>,
+    scope: Option<String>,
+    location: &'static Location<'static>,
+}
+
+impl<F, A> Reported<F, A> {
+    #[track_caller]
+    fn new(future: F, action: A, scope: Option<String>) -> Self {
+        Reported { future, action: Some(action), scope, location: Location::caller() }
+    }
+}
+
+impl<F: Future, A: __private::Finish<F::Output>> Future for Reported<F, A> {
+    type Output = A::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        // SAFETY: the inner future is never moved out of the pinned adapter and the other members
+        // are never pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        let guard = this.scope.as_ref().map(ScopeGuard::enter);
+        let output = future.poll(cx);
+        drop(guard);
+        match output {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                let action = this.action.take().expect("Reported polled after completion");
+                Poll::Ready(action.nuhound_finish(output, this.location))
+            },
+        }
+    }
+}
+
+// The outputs supported by `FutureExtension`, mirroring the types supported by `ResultExtension`,
+// `OptionExtension` and `Context`.
+#[doc(hidden)]
+pub trait Outcome<T, E, M> {
+    fn nuhound_result(self) -> Result<T, E>;
+    fn nuhound_error(error: E) -> Nuhound;
+}
+
+impl<T, E: Error> Outcome<T, E, ()> for Result<T, E> {
+    fn nuhound_result(self) -> Result<T, E> {
+        self
+    }
+
+    fn nuhound_error(error: E) -> Nuhound {
+        Nuhound::from_any(&error)
+    }
+}
+
+impl<T> Outcome<T, Box<dyn Error>, __private::Boxed> for Result<T, Box<dyn Error>> {
+    fn nuhound_result(self) -> Result<T, Box<dyn Error>> {
+        self
+    }
+
+    fn nuhound_error(error: Box<dyn Error>) -> Nuhound {
+        Nuhound::from_source(error.as_ref())
+    }
+}
+
+impl<T> Outcome<T, Box<dyn Error + Send + Sync>, __private::Boxed> for Result<T, Box<dyn Error + Send + Sync>> {
+    fn nuhound_result(self) -> Result<T, Box<dyn Error + Send + Sync>> {
+        self
+    }
+
+    fn nuhound_error(error: Box<dyn Error + Send + Sync>) -> Nuhound {
+        Nuhound::from_source(error.as_ref())
+    }
+}
+
+impl<T> Outcome<T, String, __private::Text> for Result<T, String> {
+    fn nuhound_result(self) -> Result<T, String> {
+        self
+    }
+
+    fn nuhound_error(error: String) -> Nuhound {
+        Nuhound::new(error)
+    }
+}
+
+impl<'a, T> Outcome<T, &'a str, __private::Text> for Result<T, &'a str> {
+    fn nuhound_result(self) -> Result<T, &'a str> {
+        self
+    }
+
+    fn nuhound_error(error: &'a str) -> Nuhound {
+        Nuhound::new(error)
+    }
+}
+
+impl<T> Outcome<T, Nuhound, ()> for Option<T> {
+    fn nuhound_result(self) -> Result<T, Nuhound> {
+        self.ok_or_else(|| Nuhound::new("Option::None detected"))
+    }
+
+    fn nuhound_error(error: Nuhound) -> Nuhound {
+        error
+    }
+}
+
+// The conversions applied by `Reported` once the inner future completes
+#[doc(hidden)]
+pub trait Finish<R> {
+    type Output;
+    fn nuhound_finish(self, output: R, location: &'static Location<'static>) -> Self::Output;
+}
+
+#[doc(hidden)]
+pub struct Reporter<O, K>(pub(crate) O, pub(crate) PhantomData<fn() -> K>);
+
+#[doc(hidden)]
+pub struct Easy<K>(pub(crate) PhantomData<fn() -> K>);
+
+#[doc(hidden)]
+pub struct Contextual<C, K>(pub(crate) C, pub(crate) PhantomData<fn() -> K>);
+
+impl<R: Outcome<T, E, M>, T, E, M, O: FnOnce(E) -> Nuhound> Finish<R> for Reporter<O, (T, E, M)> {
+    type Output = Report<T>;
+
+    fn nuhound_finish(self, output: R, location: &'static Location<'static>) -> Report<T> {
+        output.nuhound_result().map_err(|e| (self.0)(e).locate(location))
+    }
+}
+
+impl<R: Outcome<T, E, M>, T, E, M> Finish<R> for Easy<(T, E, M)> {
+    type Output = Report<T>;
+
+    fn nuhound_finish(self, output: R, location: &'static Location<'static>) -> Report<T> {
+        output.nuhound_result().map_err(|e| R::nuhound_error(e).locate(location))
+    }
+}
+
+impl<R: Outcome<T, E, M>, T, E, M, C: fmt::Display> Finish<R> for Contextual<C, (T, E, M)> {
+    type Output = Report<T>;
+
+    fn nuhound_finish(self, output: R, location: &'static Location<'static>) -> Report<T> {
+        output.nuhound_result().map_err(|e| Nuhound::located(self.0, location).caused_by(R::nuhound_error(e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kind, here, scope};
+    use std::task::Waker;
+    use std::thread;
+
+    // Polls a future to completion on the current thread
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut cx = TaskContext::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    // A future that is pending a number of times before it completes
+    struct Pending(usize);
+
+    impl Future for Pending {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut TaskContext<'_>) -> Poll<()> {
+            match self.0 {
+                0 => Poll::Ready(()),
+                _ => {
+                    self.0 -= 1;
+                    Poll::Pending
+                },
+            }
+        }
+    }
+
+    async fn work(id: u32) -> Report<u32> {
+        let innermost = || crate::scope::snapshot().and_then(|frames| frames.last().cloned());
+        let first = innermost();
+        Pending(2).await;
+        let e = here!(Root, "job {id} failed");
+        assert_eq!(innermost(), first);
+        Err(e)
+    }
+
+    #[test]
+    fn test_01() {
+        let io = async { Err::<u8, _>(std::io::Error::new(std::io::ErrorKind::NotFound, "gone")) };
+        let e = block_on(io.easy()).unwrap_err();
+        assert_eq!(e.kind(), Some(Kind::NotFound));
+        assert_eq!(block_on(async { Ok::<u8, String>(4) }.easy()), Ok(4));
+        let e = block_on(async { Err::<u8, &str>("bad") }.report(|e| Nuhound::new(format!("{e} input")))).unwrap_err();
+        assert_eq!(e.message(), "bad input");
+        let e = block_on(async { None::<u8> }.context("looking up user")).unwrap_err();
+        assert!(e.to_string().ends_with("looking up user"));
+        assert_eq!(e.chain().nth(1).unwrap().message(), "Option::None detected");
+        let boxed = async { Err::<u8, Box<dyn Error + Send + Sync>>("boxed".into()) };
+        assert_eq!(block_on(boxed.easy()).unwrap_err().message(), "boxed");
+        let nested = async { Err::<u8, _>(Nuhound::new("inner").with_code("E1")) };
+        assert_eq!(block_on(nested.context("outer")).unwrap_err().chain().nth(1).unwrap().code(), Some("E1"));
+    }
+
+    #[test]
+    fn test_02() {
+        let _guard = scope!("running tests");
+        let e = block_on(in_scope("handling request 42", work(42))).unwrap_err();
+        let values: Vec<String> = e.trace().split('\n').map(String::from).collect();
+        assert!(values[0].starts_with(" 0: ") && values[0].ends_with("handling request 42"));
+        assert!(values[1].starts_with("    in ") && values[1].ends_with("running tests"));
+        assert!(values[2].ends_with("job 42 failed"));
+        assert_eq!(values[3..], ["    in handling request 42".to_string(), values[1].clone()]);
+        assert_eq!(crate::scope::snapshot().map(|frames| frames.len()), Some(1));
+
+        // The scope is entered on whichever thread polls the future
+        let mut future = Box::pin(in_scope("moving", work(7)));
+        let mut cx = TaskContext::from_waker(Waker::noop());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        let e = thread::spawn(move || block_on(future)).join().unwrap().unwrap_err();
+        assert_eq!(e.chain().nth(1).unwrap().frames()[0].description(), "moving");
+        assert_eq!(block_on(in_scope("quiet", async { Ok::<u8, Nuhound>(1) })), Ok(1));
+    }
+}
@@ -0,0 +1,166 @@
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+
+use crate::Nuhound;
+
+// The digits of an id, which leave out the letters easily mistaken for digits
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+thread_local! {
+    // The state of the id source of the current thread
+    static STATE: Cell<u64> = Cell::new(seeded(RandomState::new().build_hasher().finish()));
+}
+
+/// A short id that identifies an error. Every Nuhound error is given a new id when it is created
+/// and the links that wrap it with `caused_by()`, including those created by the `here!`,
+/// `examine!` and `convert!` macros, inherit the id of their source. Showing the id to a user
+/// with `{:#}` lets the message be matched with the full trace in a log.
+///
+/// Ids are produced by a source on each thread that can be seeded so that tests see the same
+/// ids on every run.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{ErrorId, Nuhound};
+///
+/// ErrorId::seed(7);
+/// let root = Nuhound::new("disk full");
+/// let e = Nuhound::new("Top level failure").caused_by(root);
+/// let shown = format!("{:#}", e);
+/// assert_eq!(shown, "Top level failure (ref: T7XT-YZ)");
+/// assert_eq!(ErrorId::extract(&shown), [e.id()]);
+/// assert_eq!(e.id(), e.chain().last().unwrap().id());
+///
+/// ErrorId::seed(7);
+/// assert_eq!(Nuhound::new("again").id(), e.id());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ErrorId(u32);
+
+impl ErrorId {
+    /// Seeds the id source of the current thread so that the ids that follow are always the
+    /// same.
+    pub fn seed(seed: u64) {
+        STATE.with(|state| state.set(seeded(seed)));
+    }
+
+    /// Returns every id found in the text, such as a message shown to a user, a trace or a JSON
+    /// rendering, in the order they appear.
+    pub fn extract(text: &str) -> Vec<ErrorId> {
+        let bytes = text.as_bytes();
+        let mut ids = Vec::new();
+        for start in 0..bytes.len().saturating_sub(6) {
+            let bounded = |index: Option<usize>| index.and_then(|index| bytes.get(index)).is_none_or(|byte| !byte.is_ascii_alphanumeric());
+            if bounded(start.checked_sub(1)) && bounded(Some(start + 7))
+                && let Ok(id) = text.get(start..start + 7).unwrap_or_default().parse()
+            {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    // Returns the next id of the current thread
+    pub(crate) fn next() -> Self {
+        STATE.with(|state| {
+            // xorshift64*
+            let mut x = state.get();
+            x ^= x >> 12;
+            x ^= x << 25;
+            x ^= x >> 27;
+            state.set(x);
+            ErrorId((x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 34) as u32)
+        })
+    }
+}
+
+impl fmt::Display for ErrorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digit = |index: u32| ALPHABET[(self.0 >> (5 * (5 - index)) & 31) as usize] as char;
+        let text: String = (0..6).map(digit).collect();
+        write!(f, "{}-{}", &text[..4], &text[4..])
+    }
+}
+
+impl fmt::Debug for ErrorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ErrorId({})", self)
+    }
+}
+
+impl FromStr for ErrorId {
+    type Err = Nuhound;
+
+    /// Parses an id in the form shown by `Display`, such as `7F3K-Q2`.
+    fn from_str(text: &str) -> Result<Self, Nuhound> {
+        let bytes = text.as_bytes();
+        if bytes.len() != 7 || bytes[4] != b'-' {
+            return Err(Nuhound::new(format!("'{}' is not an error id", text)));
+        }
+        bytes[..4].iter().chain(&bytes[5..]).try_fold(0, |id, byte| {
+            match ALPHABET.iter().position(|digit| digit == byte) {
+                Some(value) => Ok(id << 5 | value as u32),
+                None => Err(Nuhound::new(format!("'{}' is not an error id", text))),
+            }
+        }).map(ErrorId)
+    }
+}
+
+// Turns a seed into a state for the xorshift generator, which must not be zero
+fn seeded(seed: u64) -> u64 {
+    match seed {
+        0 => 0x9E37_79B9_7F4A_7C15,
+        seed => seed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::here;
+    use std::thread;
+
+    #[test]
+    fn test_01() {
+        ErrorId::seed(42);
+        let ids: Vec<ErrorId> = (0..3).map(|_| ErrorId::next()).collect();
+        ErrorId::seed(42);
+        assert_eq!(ids, [ErrorId::next(), ErrorId::next(), ErrorId::next()]);
+        assert!(ids[0] != ids[1] && ids[1] != ids[2]);
+        let other = thread::spawn(|| {
+            ErrorId::seed(42);
+            ErrorId::next()
+        }).join().unwrap();
+        assert_eq!(other, ids[0]);
+
+        assert_eq!(ErrorId(0).to_string(), "0000-00");
+        assert_eq!(ErrorId((1 << 30) - 1).to_string(), "ZZZZ-ZZ");
+        assert_eq!("7F3K-Q2".parse::<ErrorId>().unwrap().to_string(), "7F3K-Q2");
+        assert!("7F3K-QI".parse::<ErrorId>().is_err() && "7F3KQ2".parse::<ErrorId>().is_err());
+        assert!("7F-K-Q2".parse::<ErrorId>().is_err() && "-------".parse::<ErrorId>().is_err());
+        assert_eq!(ErrorId::extract("----------------\n7F3K-Q2\n----------------").len(), 1);
+        assert_eq!(format!("{:?}", ErrorId(33)), "ErrorId(0000-11)");
+    }
+
+    #[test]
+    fn test_02() {
+        ErrorId::seed(1);
+        let root = Nuhound::new("disk full");
+        let e = here!(root.clone(), "saving failed");
+        let e = Nuhound::link("Top level failure", e);
+        assert_eq!(e.id(), root.id());
+        assert!(format!("{:#}", e).ends_with(&format!("Top level failure (ref: {})", root.id())));
+        let log = format!("ERROR [{}] {} {}", e.id(), e.to_json(), Nuhound::new("other").id());
+        let ids = ErrorId::extract(&log);
+        assert_eq!(ids[..2], [root.id(), root.id()]);
+        assert_eq!(ids.len(), 3);
+        assert!(ErrorId::extract("ABCDE-FG 7F3K-Q2x x7F3K-Q2 ref:7F3K-Q2.").len() == 1);
+        let aggregate = Nuhound::new("all failed").with_cause(e.clone());
+        assert_ne!(aggregate.id(), e.id());
+        assert_eq!(aggregate.causes()[0].id(), e.id());
+    }
+}
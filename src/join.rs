@@ -0,0 +1,168 @@
+use std::any::Any;
+use std::panic::Location;
+use std::thread::{JoinHandle, Thread};
+
+use crate::{Nuhound, Report, __private, is_nuhound};
+
+impl Nuhound {
+    /// Create a Nuhound error from the payload of a panic, such as the error returned by
+    /// `JoinHandle::join()` or `std::panic::catch_unwind()`. A Nuhound payload raised with
+    /// `std::panic::panic_any()` is recovered intact and a `&str` or `String` payload, as raised
+    /// by `panic!()`, becomes the message of the error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::Nuhound;
+    ///
+    /// let payload = std::panic::catch_unwind(|| panic!("index {} out of range", 7)).unwrap_err();
+    /// assert_eq!(Nuhound::from_panic(payload.as_ref()).message(), "index 7 out of range");
+    /// ```
+    pub fn from_panic(payload: &(dyn Any + Send)) -> Self {
+        if is_nuhound(payload) && let Some(e) = payload.downcast_ref::<Nuhound>() {
+            return e.clone();
+        }
+        match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+            (Some(text), _) => Nuhound::new(text),
+            (_, Some(text)) => Nuhound::new(text),
+            _ => Nuhound::new("panic with a payload that is not a message"),
+        }
+    }
+}
+
+/// Provides `join_easy()` and `join_report()` to `std::thread::JoinHandle`. A panic in the thread
+/// is converted into a Nuhound error with [`Nuhound::from_panic`] and every error that crosses
+/// from the thread is given a link naming the thread, so that a trace shows where the error
+/// crossed threads.
+///
+/// When the disclose feature is enabled the location of the caller is recorded on the link that
+/// names the thread.
+///
+/// # Example
+///
+/// ```
+/// use std::thread;
+///
+/// use nuhound::{Report, here, JoinExtension};
+///
+/// let handle = thread::Builder::new().name("loader".into()).spawn(|| -> Report<u32> {
+///     Err(here!(Root, "cache is corrupt"))
+/// }).unwrap();
+/// let e = handle.join_report().unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: thread 'loader' returned an error
+/// //  1: cache is corrupt
+///
+/// let handle = thread::Builder::new().name("parser".into()).spawn(|| -> u32 {
+///     panic!("unexpected token");
+/// }).unwrap();
+/// let e = handle.join_easy().unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: thread 'parser' panicked
+/// //  1: unexpected token
+/// ```
+pub trait JoinExtension<T> {
+    /// Waits for the thread to finish and returns its value, or an error if the thread panicked.
+    #[track_caller]
+    fn join_easy(self) -> Report<T>;
+
+    /// Waits for a thread that returns a [`Report`] to finish and returns its value, or an error
+    /// if the thread returned an error or panicked.
+    #[track_caller]
+    fn join_report<U>(self) -> Report<U>
+    where
+        T: __private::Joined<U>;
+}
+
+impl<T> JoinExtension<T> for JoinHandle<T> {
+    #[track_caller]
+    fn join_easy(self) -> Report<T> {
+        let location = Location::caller();
+        let name = name_of(self.thread());
+        self.join().map_err(|payload| panicked(&name, payload.as_ref(), location))
+    }
+
+    #[track_caller]
+    fn join_report<U>(self) -> Report<U>
+    where
+        T: __private::Joined<U>,
+    {
+        let location = Location::caller();
+        let name = name_of(self.thread());
+        match self.join() {
+            Ok(report) => report.nuhound_joined().map_err(|e| {
+                Nuhound::located(format!("thread {} returned an error", name), location).caused_by(e)
+            }),
+            Err(payload) => Err(panicked(&name, payload.as_ref(), location)),
+        }
+    }
+}
+
+// The thread results that `join_report()` can flatten
+#[doc(hidden)]
+pub trait Joined<U> {
+    fn nuhound_joined(self) -> Report<U>;
+}
+
+impl<U> Joined<U> for Report<U> {
+    fn nuhound_joined(self) -> Report<U> {
+        self
+    }
+}
+
+// Names a thread for the link that marks where an error crossed threads
+fn name_of(thread: &Thread) -> String {
+    match thread.name() {
+        Some(name) => format!("'{}'", name),
+        None => format!("{:?}", thread.id()),
+    }
+}
+
+// Creates the link for a panic in the named thread
+fn panicked(name: &str, payload: &(dyn Any + Send), location: &'static Location<'static>) -> Nuhound {
+    Nuhound::located(format!("thread {} panicked", name), location).caused_by(Nuhound::from_panic(payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic;
+    use std::thread;
+
+    #[test]
+    fn test_01() {
+        let e = thread::Builder::new().name("worker-1".into()).spawn(|| -> u8 {
+            panic::panic_any(Nuhound::new("disk failed").caused_by(Nuhound::new("bad sector").with_code("E7")))
+        }).unwrap().join_easy().unwrap_err();
+        let values: Vec<&str> = e.chain().map(|link| link.message()).collect();
+        assert!(values[0].ends_with("thread 'worker-1' panicked"));
+        assert_eq!(values[1..], ["disk failed", "bad sector"]);
+        assert_eq!(e.chain().nth(2).unwrap().code(), Some("E7"));
+
+        let e = thread::spawn(|| -> u8 { panic!("owned {}", "message") }).join_easy().unwrap_err();
+        assert!(e.message().starts_with("thread ThreadId("));
+        assert_eq!(e.chain().nth(1).unwrap().message(), "owned message");
+        let e = thread::spawn(|| -> u8 { panic::panic_any(42) }).join_easy().unwrap_err();
+        assert_eq!(e.chain().nth(1).unwrap().message(), "panic with a payload that is not a message");
+        assert_eq!(thread::spawn(|| 5).join_easy(), Ok(5));
+    }
+
+    #[test]
+    fn test_02() {
+        let e = thread::Builder::new().name("outer".into()).spawn(|| {
+            let inner = thread::Builder::new().name("inner".into()).spawn(|| -> Report<u8> {
+                Err(Nuhound::new("timed out"))
+            }).unwrap();
+            inner.join_report()
+        }).unwrap().join_report().unwrap_err();
+        let values: Vec<&str> = e.chain().map(|link| link.message()).collect();
+        assert!(values[0].ends_with("thread 'outer' returned an error"));
+        assert!(values[1].ends_with("thread 'inner' returned an error"));
+        assert_eq!(values[2], "timed out");
+        let e = thread::spawn(|| -> Report<u8> { panic!("gone") }).join_report().unwrap_err();
+        assert!(e.message().ends_with("panicked"));
+        assert_eq!(thread::spawn(|| Ok::<_, Nuhound>(3)).join_report(), Ok(3));
+    }
+}
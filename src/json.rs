@@ -0,0 +1,186 @@
+use std::fmt::Write;
+
+use crate::Nuhound;
+
+impl Nuhound {
+    /// Renders the error chain as JSON so that it can be passed to logging and monitoring tools.
+    ///
+    /// Each link is an object holding its `message` and, where present, its `public` message,
+    /// `location`, `kind` and `code`, with any named values in its `fields` member. The cause of a
+    /// link is held in its `source` member and any lists of causes or suppressed errors are held
+    /// in its `causes` and `suppressed` members. A snippet of the input is held in its `snippet`
+    /// member, which gives the name of the input and the position of each label. The scopes
+    /// entered when the link was created are held in its `frames` member, outermost first, each
+    /// with its `description` and, where present, its `location`. The outermost object and each
+    /// of the causes and suppressed errors hold the [`ErrorId`](crate::ErrorId) of their chain in
+    /// an `id` member.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{ErrorId, Kind, Nuhound};
+    ///
+    /// ErrorId::seed(7);
+    /// let e = Nuhound::new("Cannot start")
+    ///     .caused_by(Nuhound::new("Config file missing").with_kind(Kind::NotFound).with_code("CFG001"));
+    /// assert_eq!(
+    ///     e.to_json(),
+    ///     r#"{"message":"Cannot start","id":"XPJ6-RX","source":{"message":"Config file missing","kind":"NotFound","code":"CFG001"}}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json, true);
+        json
+    }
+
+    // Appends this link and its source to the JSON text, with the id when the link starts a chain
+    fn write_json(&self, json: &mut String, with_id: bool) {
+        json.push_str("{\"message\":");
+        quote(json, &self.message);
+        if with_id {
+            json.push_str(",\"id\":");
+            quote(json, &self.id().to_string());
+        }
+        if let Some(public) = self.public_message() {
+            json.push_str(",\"public\":");
+            quote(json, public);
+        }
+        if let Some(location) = self.location {
+            json.push_str(",\"location\":");
+            quote(json, &format!("{}:{}:{}", location.file(), location.line(), location.column()));
+        }
+        if let Some(kind) = self.kind {
+            json.push_str(",\"kind\":");
+            quote(json, &kind.to_string());
+        }
+        if let Some(code) = self.code() {
+            json.push_str(",\"code\":");
+            quote(json, code);
+        }
+        if self.fields().next().is_some() {
+            json.push_str(",\"fields\":{");
+            for (index, (name, value)) in self.fields().enumerate() {
+                if index > 0 {
+                    json.push(',');
+                }
+                quote(json, name);
+                json.push(':');
+                quote(json, value);
+            }
+            json.push('}');
+        }
+        write_list(json, "causes", self.causes());
+        write_list(json, "suppressed", self.suppressed());
+        if let Some(snippet) = self.snippet() {
+            json.push_str(",\"snippet\":{\"name\":");
+            quote(json, snippet.name());
+            json.push_str(",\"labels\":[");
+            for (index, label) in snippet.labels().iter().enumerate() {
+                let (line, column) = snippet.position(label.range().start);
+                if index > 0 {
+                    json.push(',');
+                }
+                write!(json, "{{\"start\":{},\"end\":{},\"line\":{},\"column\":{},\"message\":",
+                    label.range().start, label.range().end, line, column).unwrap();
+                quote(json, label.message());
+                json.push('}');
+            }
+            json.push_str("]}");
+        }
+        if !self.frames().is_empty() {
+            json.push_str(",\"frames\":[");
+            for (index, frame) in self.frames().iter().enumerate() {
+                if index > 0 {
+                    json.push(',');
+                }
+                json.push_str("{\"description\":");
+                quote(json, frame.description());
+                if let Some(location) = frame.location() {
+                    json.push_str(",\"location\":");
+                    quote(json, &format!("{}:{}:{}", location.file(), location.line(), location.column()));
+                }
+                json.push('}');
+            }
+            json.push(']');
+        }
+        if let Some(source) = &self.source {
+            json.push_str(",\"source\":");
+            source.write_json(json, false);
+        }
+        json.push('}');
+    }
+}
+
+// Appends a named list of errors unless the list is empty
+fn write_list(json: &mut String, name: &str, list: &[Nuhound]) {
+    if list.is_empty() {
+        return;
+    }
+    json.push_str(",\"");
+    json.push_str(name);
+    json.push_str("\":[");
+    for (index, item) in list.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        item.write_json(json, true);
+    }
+    json.push(']');
+}
+
+// Appends the text as a JSON string escaping any characters that JSON doesn't allow
+fn quote(json: &mut String, text: &str) {
+    json.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => write!(json, "\\u{:04x}", ch as u32).unwrap(),
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kind, ResultExtension};
+    use regex::Regex;
+
+    // Removes the ids, which differ from run to run, from the JSON text
+    fn without_ids(json: &str) -> String {
+        Regex::new(r#","id":"[0-9A-Z]{4}-[0-9A-Z]{2}""#).unwrap().replace_all(json, "").to_string()
+    }
+
+    #[test]
+    fn test_01() {
+        let e = Nuhound::new("Say \"hi\"\n\tC:\\temp \u{1}")
+            .caused_by(Nuhound::new("inner").with_code(42));
+        assert!(e.to_json().starts_with(&format!(r#"{{"message":"Say \"hi\"\n\tC:\\temp \u0001","id":"{}","#, e.id())));
+        assert_eq!(without_ids(&e.to_json()), r#"{"message":"Say \"hi\"\n\tC:\\temp \u0001","source":{"message":"inner","code":"42"}}"#);
+        let e = Nuhound::new("all").with_cause(Nuhound::new("one")).with_cause(e);
+        assert!(without_ids(&e.to_json()).starts_with(r#"{"message":"all","causes":[{"message":"one"},{"message":"Say "#));
+        assert_eq!(Regex::new(r#""id":"#).unwrap().find_iter(&e.to_json()).count(), 3);
+        let e = Nuhound::new("failed").with_suppressed(Nuhound::new("cleanup"));
+        assert_eq!(without_ids(&e.to_json()), r#"{"message":"failed","suppressed":[{"message":"cleanup"}]}"#);
+        let e = Nuhound::new("bad").with_field("path", "a[1]").with_field("line", 3);
+        assert_eq!(without_ids(&e.to_json()), r#"{"message":"bad","fields":{"path":"a[1]","line":"3"}}"#);
+    }
+
+    #[test]
+    fn test_02() {
+        let e = "NaN".parse::<u32>().easy().unwrap_err().with_kind(Kind::InvalidData);
+        let json = without_ids(&e.to_json());
+        if cfg!(feature = "disclose") {
+            let re = Regex::new(r#"^\{"message":"invalid digit found in string","location":"src[\\/]+json\.rs:\d+:\d+","kind":"InvalidData"\}$"#).unwrap();
+            assert!(re.is_match(&json));
+        } else {
+            assert_eq!(json, r#"{"message":"invalid digit found in string","kind":"InvalidData"}"#);
+        }
+    }
+}
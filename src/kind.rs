@@ -0,0 +1,80 @@
+use std::fmt;
+use std::io;
+
+/// A broad classification that can be attached to any link in a Nuhound error chain.
+///
+/// The variants deliberately mirror the most commonly used members of [`std::io::ErrorKind`] so
+/// that a kind survives a round trip through [`std::io::Error`]. Any `io::ErrorKind` that has no
+/// direct counterpart is mapped to `Kind::Other`.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Kind, Nuhound};
+///
+/// let e = Nuhound::new("Config file missing").with_kind(Kind::NotFound);
+/// assert_eq!(e.kind(), Some(Kind::NotFound));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Kind {
+    NotFound,
+    PermissionDenied,
+    AlreadyExists,
+    InvalidInput,
+    InvalidData,
+    TimedOut,
+    Interrupted,
+    WouldBlock,
+    UnexpectedEof,
+    Unsupported,
+    OutOfMemory,
+    ResourceBusy,
+    Other,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl From<io::ErrorKind> for Kind {
+    fn from(kind: io::ErrorKind) -> Kind {
+        match kind {
+            io::ErrorKind::NotFound => Kind::NotFound,
+            io::ErrorKind::PermissionDenied => Kind::PermissionDenied,
+            io::ErrorKind::AlreadyExists => Kind::AlreadyExists,
+            io::ErrorKind::InvalidInput => Kind::InvalidInput,
+            io::ErrorKind::InvalidData => Kind::InvalidData,
+            io::ErrorKind::TimedOut => Kind::TimedOut,
+            io::ErrorKind::Interrupted => Kind::Interrupted,
+            io::ErrorKind::WouldBlock => Kind::WouldBlock,
+            io::ErrorKind::UnexpectedEof => Kind::UnexpectedEof,
+            io::ErrorKind::Unsupported => Kind::Unsupported,
+            io::ErrorKind::OutOfMemory => Kind::OutOfMemory,
+            io::ErrorKind::ResourceBusy => Kind::ResourceBusy,
+            _ => Kind::Other,
+        }
+    }
+}
+
+impl From<Kind> for io::ErrorKind {
+    fn from(kind: Kind) -> io::ErrorKind {
+        match kind {
+            Kind::NotFound => io::ErrorKind::NotFound,
+            Kind::PermissionDenied => io::ErrorKind::PermissionDenied,
+            Kind::AlreadyExists => io::ErrorKind::AlreadyExists,
+            Kind::InvalidInput => io::ErrorKind::InvalidInput,
+            Kind::InvalidData => io::ErrorKind::InvalidData,
+            Kind::TimedOut => io::ErrorKind::TimedOut,
+            Kind::Interrupted => io::ErrorKind::Interrupted,
+            Kind::WouldBlock => io::ErrorKind::WouldBlock,
+            Kind::UnexpectedEof => io::ErrorKind::UnexpectedEof,
+            Kind::Unsupported => io::ErrorKind::Unsupported,
+            Kind::OutOfMemory => io::ErrorKind::OutOfMemory,
+            Kind::ResourceBusy => io::ErrorKind::ResourceBusy,
+            Kind::Other => io::ErrorKind::Other,
+        }
+    }
+}
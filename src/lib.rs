@@ -6,9 +6,9 @@
 //! - Converting Result::Err and Option::None values to a single nuhound type error;
 //! - Creating an error chain that can help pinpoint the source of the error;
 //! - Providing a `disclose` feature that enhances error messages by including the filename, line
-//! number and column number of the source file that caused the error. This functionality is
-//! provided by the `here!`, `convert!`, `examine!` and `custom!` macros when the `disclose`
-//! feature is enabled;
+//!   number and column number of the source file that caused the error. This functionality is
+//!   provided by the `here!`, `convert!`, `examine!`, `custom!`, `ensure!` and `bail!` macros, as
+//!   well as the `easy`, `report` and `context` methods, when the `disclose` feature is enabled;
 //! - Simplifying error handling in a concise and consistent Rust style.
 //! - Providing a simple implementation that requires minimal changes to your coding experience.
 //! 
@@ -18,8 +18,10 @@
 //! ## To help diagnose errors, use the disclose feature when compiling.
 //! ## This ensures that the source file name and line number are displayed
 //! ## when using the here!, convert!, examine! and custom! macros.
+//! ## Enabling nuhound/disclose as well does the same for the easy, report
+//! ## and context methods.
 //! ## example usage: cargo build --features=disclose
-//! disclose = []
+//! disclose = ["nuhound/disclose"]
 //! ```
 //! ## Examples
 //!
@@ -150,6 +152,73 @@
 //! // Top level failure
 //! ```
 //!
+//! ### Public and internal messages
+//!
+//! The message of a link is meant for developers and may hold details that shouldn't be shown to
+//! the users of an application. A separate public message can be given to the `here!` and `custom!`
+//! macros with `public:`. The `render_public` method shows only the public messages and replaces the
+//! others with a generic phrase and the reference of the error, which can be matched with the full
+//! trace in a log.
+//! ```
+//! use nuhound::{Report, here, custom};
+//!
+//! fn load_profile(id: u32) -> Report<String> {
+//!     custom!("no row in table profiles for id {}", id)
+//! }
+//!
+//! fn show_profile(id: u32) -> Report<String> {
+//!     load_profile(id).map_err(|e| here!(e, public: "Your profile could not be loaded", "showing profile {} failed", id))
+//! }
+//!
+//! match show_profile(42) {
+//!     Ok(_) => unreachable!(),
+//!     Err(e) => {
+//!         eprintln!("{}", e.trace());
+//!         println!("{}", e.render_public());
+//!     },
+//! }
+//! // The trace holds every message:
+//! //  0: showing profile 42 failed
+//! //  1: no row in table profiles for id 42
+//! //
+//! // The user only sees:
+//! // Your profile could not be loaded
+//! // An internal error occurred (ref: 7F3K-Q2)
+//! ```
+//!
+//! ### ensure! and bail!
+//!
+//! The `ensure!` macro returns early with an error when a condition is false, replacing the
+//! conditional branch shown above. Without a message the error shows the failed condition and, for a
+//! comparison, the value of each operand. The `bail!` macro returns early without a condition.
+//! ```
+//! use nuhound::{Report, ensure, bail};
+//!
+//! fn my_check(items: &[u32], limit: usize) -> Report<()> {
+//!     ensure!(!items.is_empty(), "No items were given");
+//!     ensure!(items.len() <= limit);
+//!     if items.contains(&0) {
+//!         bail!("Zero is not allowed");
+//!     }
+//!     Ok(())
+//! }
+//!
+//! match my_check(&[1, 2, 3], 2) {
+//!     Ok(_) => unreachable!(),
+//!     Err(e) => {
+//!         #[cfg(feature = "disclose")]
+//!         eprintln!("{}", e.trace());
+//!         #[cfg(not(feature = "disclose"))]
+//!         eprintln!("{}", e);
+//!     },
+//! }
+//! // With the disclose feature enabled the code will emit:
+//! // 0: src/main.rs:5:5: condition failed: `items.len() <= limit` (left: 3, right: 2)
+//! //
+//! // With the disclose feature disabled the code will emit:
+//! // condition failed: `items.len() <= limit` (left: 3, right: 2)
+//! ```
+//!
 //! ### Option handling
 //!
 //! The `convert!` macro can be used with an Option to handle 'None' as a type of error. In this
@@ -242,12 +311,165 @@
 //! // Top level failure
 //! ```
 //!
+//! ### The context attribute
+//!
+//! The `context` attribute adds a message to any error returned by a function. Starting the
+//! attribute with `each` also records the location of every `?` operator in the function when the
+//! disclose feature is enabled, so there's no need to wrap each line with `examine!`.
+//! ```
+//! use nuhound::{Report, ResultExtension, context};
+//!
+//! #[context("parsing '{text}'")]
+//! fn my_parse(text: &str) -> Report<u32> {
+//!     let value = text.parse::<u32>().easy()?;
+//!     Ok(value)
+//! }
+//!
+//! #[context(each, "Top level failure")]
+//! fn layer1() -> Report<u32> {
+//!     let value = my_parse("NaN")?;
+//!     Ok(value)
+//! }
+//!
+//! match layer1() {
+//!     Ok(_) => unreachable!(),
+//!     Err(e) => {
+//!         #[cfg(feature = "disclose")]
+//!         eprintln!("{}", e.trace());
+//!         #[cfg(not(feature = "disclose"))]
+//!         eprintln!("{}", e);
+//!     },
+//! }
+//! // With the disclose feature enabled the code will emit:
+//! // 0: src/main.rs:9:1: layer1: Top level failure
+//! // 1: src/main.rs:11:32: propagated through layer1
+//! // 2: src/main.rs:3:1: my_parse: parsing 'NaN'
+//! // 3: src/main.rs:5:37: invalid digit found in string
+//! //
+//! // With the disclose feature disabled the code will emit:
+//! // layer1: Top level failure
+//! ```
+//!
+//! ### The context_block macro
+//!
+//! The `context_block` macro adds a message to any error produced by a block, which avoids the need
+//! to wrap an immediately invoked closure. The block may use the `?` operator on any `Result` or
+//! `Option` supported by the `easy` method. The macro is called `context_block!` rather than
+//! `context!` because that name is already taken by the `context` attribute.
+//! ```
+//! use nuhound::{Report, context_block};
+//!
+//! fn my_pair(text: &str) -> Report<(u32, u32)> {
+//!     let pair = context_block!("parsing pair '{text}'", {
+//!         let (left, right) = text.split_once(',')?;
+//!         Ok((left.trim().parse::<u32>()?, right.trim().parse::<u32>()?))
+//!     })?;
+//!     Ok(pair)
+//! }
+//!
+//! match my_pair("12, NaN") {
+//!     Ok(_) => unreachable!(),
+//!     Err(e) => {
+//!         #[cfg(feature = "disclose")]
+//!         eprintln!("{}", e.trace());
+//!         #[cfg(not(feature = "disclose"))]
+//!         eprintln!("{}", e);
+//!     },
+//! }
+//! // With the disclose feature enabled the code will emit:
+//! // 0: src/main.rs:4:16: parsing pair '12, NaN'
+//! // 1: invalid digit found in string
+//! //
+//! // With the disclose feature disabled the code will emit:
+//! // parsing pair '12, NaN'
+//! ```
+//!
+//! ### Deriving NuhoundError
+//!
+//! The `NuhoundError` derive turns an error enum into a Nuhound error. Each variant can be given a
+//! message, a kind and a code, which are kept by the conversion and shown by the `trace` and
+//! `to_json` methods. The `?` operator converts the enum directly into a `Report`.
+//! ```
+//! use nuhound::{Report, NuhoundError};
+//!
+//! #[derive(Debug, NuhoundError)]
+//! enum ConfigError {
+//!     #[nuhound(message = "config file {path} is missing", kind = NotFound, code = "CFG001")]
+//!     Missing { path: String, source: std::io::Error },
+//! }
+//!
+//! fn read(path: &str) -> Result<String, ConfigError> {
+//!     std::fs::read_to_string(path).map_err(|source| ConfigError::Missing { path: path.to_string(), source })
+//! }
+//!
+//! fn load() -> Report<String> {
+//!     let text = read("xuhgd56qhsl")?;
+//!     Ok(text)
+//! }
+//!
+//! match load() {
+//!     Ok(_) => unreachable!(),
+//!     Err(e) => {
+//!         eprintln!("{}", e.trace());
+//!         eprintln!("{}", e.to_json());
+//!     },
+//! }
+//! // With the disclose feature disabled the code will emit:
+//! // 0: config file xuhgd56qhsl is missing (code: CFG001, kind: NotFound)
+//! // 1: No such file or directory (os error 2) (kind: NotFound)
+//! // {"message":"config file xuhgd56qhsl is missing","kind":"NotFound","code":"CFG001","source":{"message":"No such file or directory (os error 2)","kind":"NotFound"}}
+//! ```
+//!
 
 #![allow(unused)]
+// Allows the code generated by the attribute macros to refer to ::nuhound within this crate
+extern crate self as nuhound;
 use std::error::Error;
 use std::fmt;
-pub use proc_nuhound::{examine, convert, custom};
-use std::any::Any;
+use std::io;
+pub use nuhound_macros::{context, context_block, ensure, NuhoundError};
+use std::any::{Any, TypeId};
+use std::marker::PhantomData;
+use std::panic::Location;
+use std::sync::Arc;
+
+mod kind;
+mod context;
+mod json;
+mod carrier;
+mod recover;
+mod alternative;
+mod retry;
+mod cleanup;
+mod diagnostics;
+mod validator;
+mod snippet;
+mod each;
+mod scope;
+mod future;
+mod join;
+mod supervisor;
+mod budget;
+mod metadata;
+mod id;
+pub use kind::Kind;
+pub use context::Context;
+pub use carrier::{BoolExtension, FoundExtension};
+pub use recover::ReportExtension;
+pub use alternative::{first_ok, AlternativeExtension};
+pub use retry::{retry, Backoff, Clock, RetryPolicy};
+pub use cleanup::with_cleanup;
+pub use diagnostics::{Diagnostic, Diagnostics, Severity};
+pub use validator::{PathScope, Validator};
+pub use snippet::{Label, Snippet};
+pub use each::{EachExtension, ReportEach};
+pub use scope::{Frame, ScopeGuard};
+pub use future::{in_scope, FutureExtension, Reported};
+pub use join::JoinExtension;
+pub use supervisor::{Reporter, Supervisor, SupervisorPolicy};
+pub use budget::{ErrorBudget, GroupBy};
+pub use metadata::Metadata;
+pub use id::ErrorId;
 
 /// The Report typedef is used to simplify [`Result`] enum usage when using the nuhound crate
 ///
@@ -400,6 +622,15 @@ pub type Report<T> = Result<T, Nuhound>;
 /// // This will emit:
 /// // value 23 not allowed
 /// ```
+///
+/// A message that can be shown to the users of an application may be given before the internal
+/// message with `public:`. See `Nuhound::render_public()`.
+/// ```
+/// use nuhound::here;
+///
+/// let e = here!(Root, public: "Your file could not be saved", "disk {} is full", "/dev/sda1");
+/// assert_eq!(e.public_message(), Some("Your file could not be saved"));
+/// ```
 #[macro_export]
 macro_rules! here {
     () => {
@@ -408,36 +639,244 @@ macro_rules! here {
     ( Root ) => {
         $crate::here!(Root, "unspecified error")
     };
+    ( Root, public: $public:expr, $($inform:expr),+ $(,)? ) => {
+        $crate::here!(Root, $($inform),+).with_public_message($public)
+    };
+    ( $caused_by:expr, public: $public:expr, $($inform:expr),+ $(,)? ) => {
+        $crate::here!($caused_by, $($inform),+).with_public_message($public)
+    };
     ( Root, $($inform:expr),+ ) => {{
-        let inform = format!( $($inform),+ );
+        let link = $crate::Nuhound::new(format!( $($inform),+ ));
         #[cfg(feature="disclose")]
-        let inform = format!("{}:{}:{}: {}", file!(), line!(), column!(), inform);
-        $crate::Nuhound::new(inform)
+        let link = $crate::__private::disclose(link);
+        link
     }};
     ( $caused_by:expr ) => {{
-        let cause: &dyn std::error::Error = &$caused_by;
-        match cause.source() {
-            Some(source) => $crate::here!(source, "{}", $caused_by),
-            None => $crate::here!(Root , "{}", $caused_by),
-        }
+        #[allow(unused_imports)]
+        use $crate::__private::{ChainOfNuhound, ChainOfError};
+        let link = (&$caused_by).nuhound_chain();
+        #[cfg(feature="disclose")]
+        let link = $crate::__private::disclose(link);
+        link
     }};
     ( $caused_by:expr, $($inform:expr),+ ) => {{
-        let mut cause: &dyn std::error::Error = &$caused_by;
-        let mut causes = vec![$crate::Nuhound::new(cause)];
-        while cause.source().is_some() {
-            cause = cause.source().unwrap();
-            causes.push($crate::Nuhound::new(cause));
-        }
+        #[allow(unused_imports)]
+        use $crate::__private::{ChainOfNuhound, ChainOfError};
+        let chain = (&$caused_by).nuhound_chain();
+        $crate::here!(Root, $($inform),+).caused_by(chain)
+    }};
+}
+
+/// A macro that returns a `Result::Err` holding a new Nuhound error. It takes the same message as
+/// `format!` and, when the `disclose` feature is enabled, the error records the location of the
+/// macro. A message that can be shown to the users of an application may be given before the
+/// internal message with `public:`.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, custom};
+///
+/// fn save(free: u64) -> Report<()> {
+///     if free == 0 {
+///         return custom!(public: "Your file could not be saved", "no space left on {}", "/dev/sda1");
+///     }
+///     custom!("saving is not implemented")
+/// }
+///
+/// let e = save(0).unwrap_err();
+/// assert!(e.message().ends_with("no space left on /dev/sda1"));
+/// assert_eq!(e.public_message(), Some("Your file could not be saved"));
+/// ```
+#[macro_export]
+macro_rules! custom {
+    ( public: $public:expr, $($inform:expr),+ $(,)? ) => {
+        ::core::result::Result::Err($crate::here!(Root, public: $public, $($inform),+))
+    };
+    ( $($inform:expr),+ $(,)? ) => {
+        ::core::result::Result::Err($crate::here!(Root, $($inform),+))
+    };
+}
+
+/// A macro that converts the error of a `Result`, or the `None` of an `Option`, into a Nuhound
+/// error holding the given message, which is formatted in the same way as `format!`. The original
+/// error is kept as the source of the new error. Nuhound errors, io errors carrying a Nuhound error
+/// and boxed errors keep their chain intact, while any other error is converted link by link. When
+/// the `disclose` feature is enabled the new error contains the location of the macro.
+///
+/// The macro needs either `ResultExtension` or `OptionExtension` in scope, depending on whether
+/// it is given a `Result` or an `Option`.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, ResultExtension, convert};
+///
+/// fn my_result() -> Report<u32> {
+///     let text = "NaN";
+///     let value = convert!(text.parse::<u32>(), "Oh dear - '{}' could not be converted to an integer", text)?;
+///     Ok(value)
+/// }
+///
+/// let e = my_result().unwrap_err();
+/// assert_eq!(e.message(), "Oh dear - 'NaN' could not be converted to an integer");
+/// assert_eq!(e.location().is_some(), cfg!(feature = "disclose"));
+/// assert_eq!(e.chain().nth(1).unwrap().message(), "invalid digit found in string");
+/// ```
+#[macro_export]
+macro_rules! convert {
+    ( $result:expr, $($inform:expr),+ $(,)? ) => {
+        $result.report(|cause| $crate::here!(cause, $($inform),+))
+    };
+}
+
+/// A macro that adds a message to the Nuhound error of a `Report`, or to the `None` of an
+/// `Option`, in the same way as `convert!`. It is the lighter choice when the error is already a
+/// Nuhound error, which becomes the source of the new error as it is.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, ResultExtension, custom, examine};
+///
+/// fn layer2() -> Report<u32> {
+///     custom!("Layer 2 failure")
+/// }
+///
+/// fn layer1() -> Report<u32> {
+///     let value = examine!(layer2(), "Layer 1 failure")?;
+///     Ok(value)
+/// }
+///
+/// let e = layer1().unwrap_err();
+/// let messages: Vec<&str> = e.chain().map(|link| link.message()).collect();
+/// assert_eq!(messages, ["Layer 1 failure", "Layer 2 failure"]);
+/// ```
+#[macro_export]
+macro_rules! examine {
+    ( $result:expr, $($inform:expr),+ $(,)? ) => {
+        $result.report(|cause| $crate::here!(Root, $($inform),+).caused_by(cause))
+    };
+}
+
+/// A macro that returns early with a Nuhound error. It accepts the same message as the `here!`
+/// macro, with or without a preceding cause, and converts the error with `From` so that it can be
+/// used in any function returning a `Result` whose error can be created from a Nuhound.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, bail};
+///
+/// fn generate_error(value: u32) -> Report<u32> {
+///     if value == 23 {
+///         bail!("value {value} not allowed");
+///     }
+///     match "NaN".parse::<u32>() {
+///         Ok(parsed) => Ok(parsed + value),
+///         Err(e) => bail!(e, "parsing failed"),
+///     }
+/// }
+///
+/// match generate_error(23) {
+///     Ok(_) => unreachable!(),
+///     Err(e) => println!("{e}"),
+/// }
+/// // This will emit:
+/// // value 23 not allowed
+/// ```
+#[macro_export]
+macro_rules! bail {
+    () => {
+        return ::core::result::Result::Err(::core::convert::From::from($crate::here!()))
+    };
+    ( $inform:literal $(, $args:expr)* $(,)? ) => {
+        return ::core::result::Result::Err(::core::convert::From::from($crate::here!(Root, $inform $(, $args)*)))
+    };
+    ( $caused_by:expr, $($inform:expr),+ $(,)? ) => {
+        return ::core::result::Result::Err(::core::convert::From::from($crate::here!($caused_by, $($inform),+)))
+    };
+}
 
-        let mut current = causes.pop();
-        let mut chain = current.unwrap();
-        current = causes.pop();
-        while current.is_some() {
-            chain = current.unwrap().caused_by(chain);
-            current = causes.pop();
+/// A macro that records an error with a [`Validator`] when a condition is false. The error is
+/// recorded under the current path of the validator and is created in the same way as
+/// `here!(Root, ...)`, so it contains the location of the macro when the `disclose` feature is
+/// enabled. Without a message the error shows the text of the condition. The macro evaluates to
+/// the condition so that further checks can depend on it.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Validator, validate};
+///
+/// let (name, port) = ("", 70000);
+/// let mut validator = Validator::new();
+/// validate!(validator.push_field("name"), !name.is_empty(), "must not be empty");
+/// validate!(validator.push_field("port"), port <= 65535);
+/// let e = validator.finish().unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: validation failed with 2 errors
+/// //     cause 1:
+/// //      0: must not be empty (path: name)
+/// //     cause 2:
+/// //      0: condition failed: `port <= 65535` (path: port)
+/// ```
+#[macro_export]
+macro_rules! validate {
+    ( $validator:expr, $condition:expr $(,)? ) => {{
+        let valid: bool = $condition;
+        if !valid {
+            $validator.add($crate::here!(Root, "condition failed: `{}`", ::core::stringify!($condition)));
+        }
+        valid
+    }};
+    ( $validator:expr, $condition:expr, $($inform:expr),+ $(,)? ) => {{
+        let valid: bool = $condition;
+        if !valid {
+            $validator.add($crate::here!(Root, $($inform),+));
         }
+        valid
+    }};
+}
 
-        $crate::here!(Root, $($inform),+).caused_by(chain)
+/// A macro that enters a scope describing the operation in progress on the current thread. It
+/// accepts the same arguments as `format!` and returns a [`ScopeGuard`] that ends the scope when
+/// dropped. Every Nuhound error created while the guard is alive records the scope, and `trace()`
+/// shows the scopes beneath the error, innermost first. When the `disclose` feature is enabled the
+/// scope records the location of the macro, which is shown before the description.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, here, scope};
+///
+/// fn insert(row: u32) -> Report<()> {
+///     Err(here!(Root, "disk full while inserting row {row}"))
+/// }
+///
+/// fn migrate(table: &str) -> Report<()> {
+///     let _guard = scope!("migrating table {table}");
+///     insert(7)
+/// }
+///
+/// let _guard = scope!("handling request {}", 42);
+/// let e = migrate("users").unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: disk full while inserting row 7
+/// //     in migrating table users
+/// //     in handling request 42
+/// ```
+#[macro_export]
+macro_rules! scope {
+    ( $($description:expr),+ $(,)? ) => {{
+        let description = format!( $($description),+ );
+        #[cfg(feature="disclose")]
+        let guard = $crate::__private::scope(description);
+        #[cfg(not(feature="disclose"))]
+        let guard = $crate::ScopeGuard::enter(description);
+        guard
     }};
 }
 
@@ -489,12 +928,69 @@ macro_rules! here {
 ///     Ok(_) => unreachable!(),
 /// };
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct Nuhound {
     source: Option<Box<Nuhound>>,
     message: String,
+    kind: Option<Kind>,
+    location: Option<&'static Location<'static>>,
+    id: ErrorId,
+    details: Option<Box<Details>>,
+}
+
+// The parts of a link that are seldom used. They are boxed so that a Nuhound error stays small
+// enough to be returned cheaply.
+#[derive(Debug, Clone, Default)]
+struct Details {
+    code: Option<String>,
+    causes: Vec<Nuhound>,
+    suppressed: Vec<Nuhound>,
+    fields: Vec<(String, String)>,
+    snippet: Option<Snippet>,
+    frames: Option<Arc<[Frame]>>,
+    metadata: Option<Metadata>,
+    public: Option<String>,
+}
+
+// Links compare equal whatever their id, scopes and metadata, so a link without details equals one
+// whose details only hold scopes or metadata
+impl PartialEq for Nuhound {
+    fn eq(&self, other: &Self) -> bool {
+        self.message == other.message
+            && self.kind == other.kind
+            && self.location == other.location
+            && self.details() == other.details()
+            && self.source == other.source
+    }
+}
+
+impl Eq for Nuhound {}
+
+impl PartialEq for Details {
+    fn eq(&self, other: &Self) -> bool {
+        self.code == other.code
+            && self.causes == other.causes
+            && self.suppressed == other.suppressed
+            && self.fields == other.fields
+            && self.snippet == other.snippet
+            && self.public == other.public
+    }
 }
 
+impl Eq for Details {}
+
+// The details of a link that has none
+static NO_DETAILS: Details = Details {
+    code: None,
+    causes: Vec::new(),
+    suppressed: Vec::new(),
+    fields: Vec::new(),
+    snippet: None,
+    frames: None,
+    metadata: None,
+    public: None,
+};
+
 impl Error for Nuhound {
     /// Returns the source of the current error or `None` if no source information is available.
     fn source(&self) -> Option<&(dyn Error + 'static)> {
@@ -505,9 +1001,17 @@ impl Error for Nuhound {
     }
 }
 
+// The alternate form, `{:#}`, follows the message with the id of the error
 impl fmt::Display for Nuhound {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.message)
+        match self.location {
+            Some(location) => write!(f, "{}:{}:{}: {}", location.file(), location.line(), location.column(), self.message)?,
+            None => write!(f, "{}", self.message)?,
+        }
+        match f.alternate() {
+            true => write!(f, " (ref: {})", self.id),
+            false => Ok(()),
+        }
     }
 }
 
@@ -517,9 +1021,68 @@ impl From<&str> for Nuhound {
     }
 }
 
+/// Converts a Nuhound error into a [`std::io::Error`] so that it can be returned from trait
+/// methods that must return `io::Result`, such as those of `Read`, `Write` and `BufRead`.
+///
+/// The whole Nuhound chain is kept as the inner error and the io error kind is taken from the
+/// first [`Kind`] found in the chain, defaulting to `io::ErrorKind::Other`.
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+/// use nuhound::{Kind, Nuhound};
+///
+/// let e = Nuhound::new("Cannot read header")
+///     .caused_by(Nuhound::new("File missing").with_kind(Kind::NotFound));
+/// let io_error = io::Error::from(e);
+/// assert_eq!(io_error.kind(), io::ErrorKind::NotFound);
+/// assert_eq!(io_error.to_string(), "Cannot read header");
+/// ```
+impl From<Nuhound> for io::Error {
+    fn from(value: Nuhound) -> io::Error {
+        let mut kind = value.kind;
+        let mut item = value.source.as_ref();
+        while kind.is_none() && item.is_some() {
+            let this = item.unwrap();
+            kind = this.kind;
+            item = this.source.as_ref();
+        }
+        io::Error::new(kind.unwrap_or(Kind::Other).into(), value)
+    }
+}
+
+/// Converts a [`std::io::Error`] into a Nuhound error.
+///
+/// If the io error was created from a Nuhound error then the original chain is recovered
+/// intact, otherwise the io error and its sources are converted link by link and the io error
+/// kind is retained.
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+/// use nuhound::{Kind, Nuhound};
+///
+/// let original = Nuhound::new("Top level failure").caused_by(Nuhound::new("Root cause"));
+/// let io_error = io::Error::from(original.clone());
+/// assert_eq!(Nuhound::from(io_error), original);
+///
+/// let io_error = io::Error::new(io::ErrorKind::TimedOut, "Server did not respond");
+/// assert_eq!(Nuhound::from(io_error).kind(), Some(Kind::TimedOut));
+/// ```
+impl From<io::Error> for Nuhound {
+    fn from(value: io::Error) -> Nuhound {
+        Nuhound::from_source(&value)
+    }
+}
+
 impl Nuhound {
     /// Create a Nuhound error.
     ///
+    /// The error is given a new [`ErrorId`], takes a snapshot of the scopes entered on the current
+    /// thread with the [`scope!`] macro, and captures its [`Metadata`] when capture is enabled.
+    ///
     /// # Example
     ///
     /// ```
@@ -531,29 +1094,311 @@ impl Nuhound {
         Self {
             source: None,
             message: inform.to_string(),
+            kind: None,
+            location: None,
+            id: ErrorId::next(),
+            details: match (scope::snapshot(), Metadata::capture()) {
+                (None, None) => None,
+                (frames, metadata) => Some(Box::new(Details { frames, metadata, ..Details::default() })),
+            },
         }
     }
 
-    /// Create a Nuhound error chain by appending and consolidating an existing error chain.
-    /// Each element in the chain is converted into a Nuhound type.
+    /// Create a Nuhound error chain from any error and its sources. Each element in the chain is
+    /// converted into a Nuhound type.
+    ///
+    /// Any source that is already a Nuhound error, or an [`std::io::Error`] carrying a Nuhound
+    /// error, is taken intact rather than being converted from its text.
     ///
     /// # Example
     ///
     /// ```
-    /// use nuhound::{Nuhound, is_nuhound};
-    /// use std::num::ParseIntError;
-    ///
-    /// fn generate_error() -> Result<u32, ParseIntError> {
-    ///     let text = "NaN";
-    ///     // This will fail because 'NaN' is not an integer
-    ///     let value = text.parse::<u32>()?;
-    ///     Ok(value)
-    /// }
+    /// use nuhound::Nuhound;
     ///
-    /// match generate_error() {
-    ///     Ok(_) => unreachable!(),
-    ///     Err(e) => {
-    ///         assert!(!is_nuhound(&e)); // This isn't a nuhound error
+    /// let e = "NaN".parse::<u32>().unwrap_err();
+    /// let my_error = Nuhound::from_error(&e);
+    /// assert_eq!(my_error.trace(), " 0: invalid digit found in string");
+    /// ```
+    pub fn from_error(error: &dyn Error) -> Self {
+        let mut causes = vec![Nuhound::new(error)];
+        let mut chain = None;
+        let mut item = error.source();
+        while let Some(cause) = item {
+            match Nuhound::recover(cause) {
+                Some(intact) => {
+                    chain = Some(intact);
+                    break;
+                },
+                None => causes.push(Nuhound::from_link(cause)),
+            }
+            item = cause.source();
+        }
+
+        while let Some(current) = causes.pop() {
+            chain = match chain {
+                Some(chain) => Some(current.caused_by(chain)),
+                None => Some(current),
+            };
+        }
+        chain.unwrap()
+    }
+
+    // Creates a Nuhound error that discloses the location of the caller in the same way as the
+    // macros do when the disclose feature is enabled.
+    fn located(inform: impl fmt::Display, location: &'static Location<'static>) -> Self {
+        Nuhound::new(inform).locate(location)
+    }
+
+    // Records the location when the disclose feature is enabled unless the link already discloses
+    // one, either by an earlier call or because it was created by one of the macros.
+    fn locate(mut self, location: &'static Location<'static>) -> Self {
+        if cfg!(feature = "disclose") && !self.discloses() {
+            self.location = Some(location);
+        }
+        self
+    }
+
+    // Determines whether this link discloses a location
+    fn discloses(&self) -> bool {
+        self.location.is_some()
+    }
+
+    /// Returns the source location recorded for this link by methods such as `easy()`,
+    /// `report()` and `context()`. A location is only recorded when the code is compiled with
+    /// the disclose feature enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::OptionExtension;
+    ///
+    /// let e = vec![1, 2, 3, 4].get(4).easy().unwrap_err();
+    /// match e.location() {
+    ///     Some(location) => println!("Error created at line {}", location.line()),
+    ///     None => println!("Compile with the disclose feature to record a location"),
+    /// }
+    /// ```
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        self.location
+    }
+
+    // Converts an error whose type can be inspected, recovering Nuhound chains where possible.
+    fn from_source(error: &(dyn Error + 'static)) -> Self {
+        match Nuhound::recover(error) {
+            Some(intact) => intact,
+            None => {
+                let chain = Nuhound::from_error(error);
+                match error.downcast_ref::<io::Error>() {
+                    Some(io_error) if chain.kind.is_none() => chain.with_kind(io_error.kind().into()),
+                    _ => chain,
+                }
+            },
+        }
+    }
+
+    // Converts an error of any type. A Nuhound or io error is recognised even when the type is not
+    // known to be 'static, so its chain is recovered in the same way as by from_source().
+    fn from_any<E: Error>(error: &E) -> Self {
+        match inspectable(error) {
+            Some(error) => Nuhound::from_source(error),
+            None => Nuhound::from_error(error),
+        }
+    }
+
+    // Converts an error of any type in the same way as from_any(), recording the location on the
+    // newly created link unless the chain has been recovered intact.
+    fn from_any_located<E: Error>(error: &E, location: &'static Location<'static>) -> Self {
+        match inspectable(error) {
+            Some(error) => Nuhound::from_source_located(error, location),
+            None => Nuhound::from_error(error).locate(location),
+        }
+    }
+
+    // Converts an error whose type can be inspected, recording the location on the newly created
+    // link unless the chain has been recovered intact.
+    fn from_source_located(error: &(dyn Error + 'static), location: &'static Location<'static>) -> Self {
+        match Nuhound::recover(error) {
+            Some(intact) => intact,
+            None => Nuhound::from_source(error).locate(location),
+        }
+    }
+
+    // Records the location of a '?' operator tagged by the context attribute. A new link is added
+    // to an existing Nuhound chain, any other error is converted and given the location.
+    fn site(error: &(dyn Error + 'static), function: &str, disclose: bool, location: &'static Location<'static>) -> Self {
+        match Nuhound::recover(error) {
+            Some(chain) if disclose => {
+                let mut link = Nuhound::new(format!("propagated through {}", function));
+                link.location = Some(location);
+                link.caused_by(chain)
+            },
+            Some(chain) => chain,
+            None => Nuhound::from_source(error).site_located(disclose, location),
+        }
+    }
+
+    // Records the location of a '?' operator on a newly converted link
+    fn site_located(mut self, disclose: bool, location: &'static Location<'static>) -> Self {
+        if disclose && !self.discloses() {
+            self.location = Some(location);
+        }
+        self
+    }
+
+    // Converts a single link of a chain retaining the kind of any io error.
+    fn from_link(error: &(dyn Error + 'static)) -> Self {
+        let link = Nuhound::new(error);
+        match error.downcast_ref::<io::Error>() {
+            Some(io_error) => link.with_kind(io_error.kind().into()),
+            None => link,
+        }
+    }
+
+    // Returns a copy of the Nuhound chain held by the error, if any.
+    fn recover(error: &(dyn Error + 'static)) -> Option<Self> {
+        if let Some(nuhound) = error.downcast_ref::<Nuhound>() {
+            return Some(nuhound.clone());
+        }
+        error.downcast_ref::<io::Error>()
+            .and_then(|io_error| io_error.get_ref())
+            .and_then(|inner| inner.downcast_ref::<Nuhound>())
+            .cloned()
+    }
+
+    /// Classify this link of the error chain. The kind is shown by `trace()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Kind, Nuhound};
+    ///
+    /// let e = Nuhound::new("Access refused").with_kind(Kind::PermissionDenied);
+    /// assert_eq!(e.kind(), Some(Kind::PermissionDenied));
+    /// assert_eq!(e.trace(), " 0: Access refused (kind: PermissionDenied)");
+    /// ```
+    pub fn with_kind(mut self, kind: Kind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Returns the classification of this link of the error chain or `None` if it hasn't been
+    /// classified.
+    ///
+    /// ```
+    /// use nuhound::Nuhound;
+    ///
+    /// assert_eq!(Nuhound::new("Unclassified").kind(), None);
+    /// ```
+    pub fn kind(&self) -> Option<Kind> {
+        self.kind
+    }
+
+    /// Identify this link of the error chain with a code such as the one given to a variant of an
+    /// enum deriving `NuhoundError`. The code is shown by `trace()` along with any kind.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Kind, Nuhound};
+    ///
+    /// let e = Nuhound::new("Config file missing").with_kind(Kind::NotFound).with_code("CFG001");
+    /// assert_eq!(e.code(), Some("CFG001"));
+    /// assert_eq!(e.trace(), " 0: Config file missing (code: CFG001, kind: NotFound)");
+    /// ```
+    pub fn with_code(mut self, code: impl fmt::Display) -> Self {
+        self.details_mut().code = Some(code.to_string());
+        self
+    }
+
+    /// Returns the code identifying this link of the error chain or `None` if it hasn't been
+    /// given one.
+    ///
+    /// ```
+    /// use nuhound::Nuhound;
+    ///
+    /// assert_eq!(Nuhound::new("Uncoded").code(), None);
+    /// ```
+    pub fn code(&self) -> Option<&str> {
+        self.details().code.as_deref()
+    }
+
+    // Returns the seldom used parts of the link
+    fn details(&self) -> &Details {
+        self.details.as_deref().unwrap_or(&NO_DETAILS)
+    }
+
+    // Returns the seldom used parts of the link for modification
+    fn details_mut(&mut self) -> &mut Details {
+        self.details.get_or_insert_with(Default::default)
+    }
+
+    /// Attach a named value to this link, such as the path of the field that failed validation.
+    /// Fields are shown by `trace()` and held in the `fields` member of the JSON output. Setting
+    /// a field that already exists replaces its value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::Nuhound;
+    ///
+    /// let e = Nuhound::new("must not be empty").with_field("path", "servers[2].tls.cert_path");
+    /// assert_eq!(e.field("path"), Some("servers[2].tls.cert_path"));
+    /// assert_eq!(e.trace(), " 0: must not be empty (path: servers[2].tls.cert_path)");
+    /// ```
+    pub fn with_field(mut self, name: impl fmt::Display, value: impl fmt::Display) -> Self {
+        let name = name.to_string();
+        let fields = &mut self.details_mut().fields;
+        fields.retain(|(existing, _)| *existing != name);
+        fields.push((name, value.to_string()));
+        self
+    }
+
+    /// Returns the value of the named field of this link, if any.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.details().fields.iter().find(|(existing, _)| existing == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the named values attached to this link in the order they were added.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.details().fields.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    // Displays the link followed by its code, its kind and its fields, where present
+    fn annotated(&self) -> String {
+        let mut notes = Vec::new();
+        if let Some(code) = &self.details().code {
+            notes.push(format!("code: {}", code));
+        }
+        if let Some(kind) = self.kind {
+            notes.push(format!("kind: {}", kind));
+        }
+        notes.extend(self.details().fields.iter().map(|(name, value)| format!("{}: {}", name, value)));
+        match notes.is_empty() {
+            true => self.to_string(),
+            false => format!("{} ({})", self, notes.join(", ")),
+        }
+    }
+
+    /// Create a Nuhound error chain by appending and consolidating an existing error chain.
+    /// Each element in the chain is converted into a Nuhound type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Nuhound, is_nuhound};
+    /// use std::num::ParseIntError;
+    ///
+    /// fn generate_error() -> Result<u32, ParseIntError> {
+    ///     let text = "NaN";
+    ///     // This will fail because 'NaN' is not an integer
+    ///     let value = text.parse::<u32>()?;
+    ///     Ok(value)
+    /// }
+    ///
+    /// match generate_error() {
+    ///     Ok(_) => unreachable!(),
+    ///     Err(e) => {
+    ///         assert!(!is_nuhound(&e)); // This isn't a nuhound error
     ///         // Convert the underlying error 'e' to a Nuhound by linking
     ///         let my_error = Nuhound::link("Parse Integer failed", e);
     ///         assert!(is_nuhound(&my_error)); // This is a nuhound error
@@ -564,72 +1409,553 @@ impl Nuhound {
     pub fn link(inform: impl fmt::Display, caused_by: impl Error) -> Self {
         // Take the whole chain converting each to Nuhound along the way
         // We assume that the chain may contain non-Nuhound errors
-        let mut cause: &dyn Error = &caused_by;
-        let mut causes = vec![Nuhound::new(cause)];
-        while cause.source().is_some() {
-            cause = cause.source().unwrap();
-            causes.push(Nuhound::new(cause));
+        let chain = Nuhound::from_error(&caused_by);
+
+        // Finally add the top level message 'inform' to the chain
+        Nuhound::new(inform).caused_by(chain)
+    }
+
+    /// Add a cause to an existing Nuhound error.
+    ///
+    /// ```
+    /// use nuhound::{Nuhound, OptionExtension};
+    ///
+    /// let error_source = vec![1, 2, 3, 4].get(4).easy().unwrap_err();
+    /// let my_error = Nuhound::new("Out of bounds").caused_by(error_source);
+    /// println!("{}", my_error.trace());
+    /// // emits:
+    /// //  0: Out of bounds
+    /// //  1: Option::None detected
+    /// ```
+    pub fn caused_by(mut self, source: Nuhound) -> Self {
+        self.id = source.id;
+        self.source = Some(Box::new(source));
+        self
+    }
+ 
+    /// Add an error to the list of causes held by this link. Causes are used when a single error
+    /// stands for several independent failures, such as every alternative that was tried, and
+    /// are shown beneath the link by `trace()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::Nuhound;
+    ///
+    /// let e = Nuhound::new("No mirror responded")
+    ///     .with_cause(Nuhound::new("mirror 1 timed out"))
+    ///     .with_cause(Nuhound::new("mirror 2 refused the connection"));
+    /// assert_eq!(e.causes().len(), 2);
+    /// assert_eq!(e.trace(), " 0: No mirror responded\n    cause 1:\n     0: mirror 1 timed out\n    \
+    ///     cause 2:\n     0: mirror 2 refused the connection");
+    /// ```
+    pub fn with_cause(mut self, cause: Nuhound) -> Self {
+        self.details_mut().causes.push(cause);
+        self
+    }
+
+    /// Returns the causes held by this link in the order they were added.
+    ///
+    /// ```
+    /// use nuhound::Nuhound;
+    ///
+    /// assert!(Nuhound::new("Single failure").causes().is_empty());
+    /// ```
+    pub fn causes(&self) -> &[Nuhound] {
+        &self.details().causes
+    }
+
+    /// Add an error that occurred while handling this one, such as a failed cleanup, that would
+    /// otherwise be lost. Suppressed errors are kept apart from the source chain and are shown in
+    /// their own section by `trace()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::Nuhound;
+    ///
+    /// let e = Nuhound::new("Saving failed")
+    ///     .caused_by(Nuhound::new("Disk full"))
+    ///     .with_suppressed(Nuhound::new("Removing temporary file failed"));
+    /// assert_eq!(e.suppressed().len(), 1);
+    /// assert_eq!(e.trace(), " 0: Saving failed\n 1: Disk full\n    suppressed 1:\n     0: Removing temporary file failed");
+    /// ```
+    pub fn with_suppressed(mut self, suppressed: Nuhound) -> Self {
+        self.details_mut().suppressed.push(suppressed);
+        self
+    }
+
+    /// Returns the errors suppressed by this link in the order they were added.
+    ///
+    /// ```
+    /// use nuhound::Nuhound;
+    ///
+    /// assert!(Nuhound::new("Clean failure").suppressed().is_empty());
+    /// ```
+    pub fn suppressed(&self) -> &[Nuhound] {
+        &self.details().suppressed
+    }
+
+    /// Attach a snippet of the input that caused this error, such as a configuration file with
+    /// the offending value labelled. The snippet is rendered beneath the link by `trace()`.
+    /// Attaching another snippet replaces the first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Nuhound, Snippet};
+    ///
+    /// let snippet = Snippet::new("app.toml", "port = \"eighty\"").with_label(7..15, "expected a number");
+    /// let e = Nuhound::new("invalid configuration").with_snippet(snippet);
+    /// assert_eq!(e.trace(), " 0: invalid configuration\n    --> app.toml:1:8\n      |\n    1 | port = \"eighty\"\n      |        ^^^^^^^^ expected a number");
+    /// ```
+    pub fn with_snippet(mut self, snippet: Snippet) -> Self {
+        self.details_mut().snippet = Some(snippet);
+        self
+    }
+
+    /// Returns the snippet of the input attached to this link, if any.
+    pub fn snippet(&self) -> Option<&Snippet> {
+        self.details().snippet.as_ref()
+    }
+
+    /// Returns the scopes that were entered on the thread that created this link, outermost first.
+    /// Errors compare equal whatever their scopes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Nuhound, scope};
+    ///
+    /// let _request = scope!("handling request {}", 42);
+    /// let _table = scope!("migrating table users");
+    /// let e = Nuhound::new("disk full");
+    /// assert_eq!(e.frames().len(), 2);
+    /// ```
+    pub fn frames(&self) -> &[Frame] {
+        self.details().frames.as_deref().unwrap_or_default()
+    }
+
+    /// Give this link a message that can be shown to the users of an application, leaving the
+    /// message of the link for internal use such as logging. See `render_public()`.
+    pub fn with_public_message(mut self, public: impl fmt::Display) -> Self {
+        self.details_mut().public = Some(public.to_string());
+        self
+    }
+
+    /// Returns the message of this link that can be shown to users, if it has one.
+    pub fn public_message(&self) -> Option<&str> {
+        self.details().public.as_deref()
+    }
+
+    /// Renders the error for an audience that must not see internal details, such as the users
+    /// of a web service. The public message of each link in the chain is shown on a line of its
+    /// own and each run of links without one is replaced by a generic phrase with the reference
+    /// of the error, so that the user can quote it and the full trace can be found in a log. The
+    /// causes and suppressed errors of the links are left out.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{ErrorId, Nuhound, here};
+    ///
+    /// ErrorId::seed(3);
+    /// let e = here!(Root, "connection to db-7 refused");
+    /// let e = here!(e, "query failed: SELECT * FROM users");
+    /// let e = here!(e, public: "Your profile could not be loaded", "loading profile 42 failed");
+    /// println!("{}", e.render_public());
+    /// // This will emit:
+    /// // Your profile could not be loaded
+    /// // An internal error occurred (ref: TYQ6-NR)
+    /// assert_eq!(e.render_public(), format!("Your profile could not be loaded\nAn internal error occurred (ref: {})", e.id()));
+    /// ```
+    pub fn render_public(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let mut internal = false;
+        for link in self.chain() {
+            match link.public_message() {
+                Some(public) => {
+                    lines.push(public.to_string());
+                    internal = false;
+                },
+                None if !internal => {
+                    lines.push(format!("An internal error occurred (ref: {})", link.id()));
+                    internal = true;
+                },
+                None => (),
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Returns the id of the error. A link wrapping another with `caused_by()` has the id of the
+    /// link it wraps, so every link of a chain has the id of its root cause.
+    pub fn id(&self) -> ErrorId {
+        self.id
+    }
+
+    /// Returns the metadata captured when this link was created, if capture was enabled.
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.details().metadata.as_ref()
+    }
+
+    /// Returns the message of this link as it was given. The location disclosed by the macros is
+    /// held apart from the message and is returned by `location()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Nuhound, here};
+    ///
+    /// assert_eq!(Nuhound::new("Access refused").message(), "Access refused");
+    /// assert_eq!(here!(Root, "Access refused").message(), "Access refused");
+    /// ```
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns an iterator over the links of the error chain starting at this link and working
+    /// backwards towards the error source.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Kind, Nuhound};
+    ///
+    /// let e = Nuhound::new("Cannot start")
+    ///     .caused_by(Nuhound::new("Config file missing").with_kind(Kind::NotFound));
+    /// assert_eq!(e.chain().count(), 2);
+    /// assert!(e.chain().any(|link| link.kind() == Some(Kind::NotFound)));
+    /// ```
+    pub fn chain(&self) -> impl Iterator<Item = &Nuhound> {
+        std::iter::successors(Some(self), |link| link.source.as_deref())
+    }
+
+    /// Create a list of errors starting at the most recent error and working backwards towards the
+    /// the error source. A link that has been given a code is followed by its code and kind.
+    ///
+    /// ```
+    /// use nuhound::{Nuhound, OptionExtension};
+    ///
+    /// let error_source = vec![1, 2, 3, 4].get(4).easy().unwrap_err();
+    /// let my_error = Nuhound::new("Out of bounds").caused_by(error_source);
+    /// println!("{}", my_error.trace());
+    /// // emits:
+    /// //  0: Out of bounds
+    /// //  1: Option::None detected
+    /// ```
+    pub fn trace(&self) -> String {
+        let mut trace_list = Vec::new();
+        self.trace_into("", &mut trace_list, false);
+        trace_list.join("\n")
+    }
+
+    /// Create the same list as `trace()` with each link that captured its [`Metadata`] preceded
+    /// by the time it was created relative to the root cause of its chain.
+    ///
+    /// ```
+    /// use nuhound::{Metadata, Nuhound};
+    ///
+    /// Metadata::set_enabled(true);
+    /// let root = Nuhound::new("connection reset");
+    /// std::thread::sleep(std::time::Duration::from_millis(100));
+    /// let e = Nuhound::new("request failed").caused_by(root);
+    /// println!("{}", e.trace_timed());
+    /// // emits:
+    /// //  0: [+0.100s] request failed
+    /// //  1: [+0.000s] connection reset
+    /// ```
+    pub fn trace_timed(&self) -> String {
+        let mut trace_list = Vec::new();
+        self.trace_into("", &mut trace_list, true);
+        trace_list.join("\n")
+    }
+
+    // Adds the trace of the chain to the list. The scopes, snippet and causes of a link are shown
+    // beneath it with a deeper indent and the errors suppressed by any link follow the chain with
+    // the same indent as the causes. The scopes are left out when they are the same as those of
+    // the link before.
+    fn trace_into(&self, indent: &str, trace_list: &mut Vec<String>, timed: bool) {
+        let deeper = format!("{}    ", indent);
+        let mut frames: &[Frame] = &[];
+        let root = self.chain().filter_map(Nuhound::metadata).last().map(Metadata::instant);
+        for (n, this) in self.chain().enumerate() {
+            let elapsed = match (timed, root, this.metadata()) {
+                (true, Some(root), Some(metadata)) => format!("[+{:.3}s] ", metadata.instant().saturating_duration_since(root).as_secs_f64()),
+                _ => String::new(),
+            };
+            trace_list.push(format!("{}{:2}: {}{}", indent, n, elapsed, this.annotated()));
+            if this.frames() != frames {
+                frames = this.frames();
+                trace_list.extend(frames.iter().rev().map(|frame| format!("{}in {}", deeper, frame)));
+            }
+            if let Some(snippet) = this.snippet() {
+                trace_list.extend(snippet.render().split('\n').map(|line| format!("{}{}", deeper, line)));
+            }
+            for (index, cause) in this.causes().iter().enumerate() {
+                trace_list.push(format!("{}cause {}:", deeper, index + 1));
+                cause.trace_into(&deeper, trace_list, timed);
+            }
+        }
+        for (index, suppressed) in self.chain().flat_map(|this| this.suppressed().iter()).enumerate() {
+            trace_list.push(format!("{}suppressed {}:", deeper, index + 1));
+            suppressed.trace_into(&deeper, trace_list, timed);
+        }
+    }
+}
+
+// Support for the macros. The traits rely on method resolution preferring an exact receiver over
+// an auto-referenced one so that Nuhound and io errors are recovered intact by the `here!` macro
+// while any other error is converted link by link.
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    // Markers that distinguish the error types supported by `ResultExtension`
+    pub struct Boxed;
+    pub struct Text;
+
+    // The outputs and conversions supported by `FutureExtension`
+    pub use crate::future::{Contextual, Easy, Finish, Outcome, Reporter};
+
+    // The thread results flattened by `JoinExtension::join_report()`
+    pub use crate::join::Joined;
+
+    pub trait ChainOfNuhound {
+        fn nuhound_chain(&self) -> Nuhound;
+    }
+
+    impl ChainOfNuhound for Nuhound {
+        fn nuhound_chain(&self) -> Nuhound {
+            self.clone()
+        }
+    }
+
+    impl ChainOfNuhound for io::Error {
+        fn nuhound_chain(&self) -> Nuhound {
+            Nuhound::from_source(self)
+        }
+    }
+
+    impl ChainOfNuhound for Box<dyn Error> {
+        fn nuhound_chain(&self) -> Nuhound {
+            Nuhound::from_source(self.as_ref())
+        }
+    }
+
+    impl ChainOfNuhound for Box<dyn Error + Send + Sync> {
+        fn nuhound_chain(&self) -> Nuhound {
+            Nuhound::from_source(self.as_ref())
+        }
+    }
+
+    impl ChainOfNuhound for String {
+        fn nuhound_chain(&self) -> Nuhound {
+            Nuhound::new(self)
+        }
+    }
+
+    impl ChainOfNuhound for &str {
+        fn nuhound_chain(&self) -> Nuhound {
+            Nuhound::new(self)
+        }
+    }
+
+    // Records the location of a '?' operator in a function with the `context(each, ...)` attribute
+    pub trait Site<T, M = ()> {
+        #[track_caller]
+        fn nuhound_site(self, function: &str, disclose: bool) -> Report<T>;
+    }
+
+    impl<T, E: Error + 'static> Site<T> for Result<T, E> {
+        #[track_caller]
+        fn nuhound_site(self, function: &str, disclose: bool) -> Report<T> {
+            match self {
+                Ok(val) => Ok(val),
+                Err(e) => Err(Nuhound::site(&e, function, disclose, Location::caller())),
+            }
+        }
+    }
+
+    impl<T> Site<T, Boxed> for Result<T, Box<dyn Error>> {
+        #[track_caller]
+        fn nuhound_site(self, function: &str, disclose: bool) -> Report<T> {
+            match self {
+                Ok(val) => Ok(val),
+                Err(e) => Err(Nuhound::site(e.as_ref(), function, disclose, Location::caller())),
+            }
+        }
+    }
+
+    impl<T> Site<T, Boxed> for Result<T, Box<dyn Error + Send + Sync>> {
+        #[track_caller]
+        fn nuhound_site(self, function: &str, disclose: bool) -> Report<T> {
+            match self {
+                Ok(val) => Ok(val),
+                Err(e) => Err(Nuhound::site(e.as_ref(), function, disclose, Location::caller())),
+            }
+        }
+    }
+
+    impl<T> Site<T, Text> for Result<T, String> {
+        #[track_caller]
+        fn nuhound_site(self, function: &str, disclose: bool) -> Report<T> {
+            let location = Location::caller();
+            self.map_err(|e| Nuhound::new(e).site_located(disclose, location))
+        }
+    }
+
+    impl<T> Site<T, Text> for Result<T, &str> {
+        #[track_caller]
+        fn nuhound_site(self, function: &str, disclose: bool) -> Report<T> {
+            let location = Location::caller();
+            self.map_err(|e| Nuhound::new(e).site_located(disclose, location))
+        }
+    }
+
+    impl<T> Site<T, Text> for Option<T> {
+        #[track_caller]
+        fn nuhound_site(self, function: &str, disclose: bool) -> Report<T> {
+            let location = Location::caller();
+            self.ok_or_else(|| Nuhound::new("Option::None detected").site_located(disclose, location))
+        }
+    }
+
+    // Enters a scope that records the location of the `scope!` macro when the disclose feature of
+    // the crate using the macro is enabled
+    #[track_caller]
+    pub fn scope(description: String) -> ScopeGuard {
+        ScopeGuard::located(description, Location::caller())
+    }
+
+    // Records the location of the macro that created or converted the link, unless it already
+    // has one, when the disclose feature of the crate using the macro is enabled
+    #[track_caller]
+    pub fn disclose(link: Nuhound) -> Nuhound {
+        link.site_located(true, Location::caller())
+    }
+
+    // Creates the link for a variant of an enum deriving `NuhoundError`
+    #[track_caller]
+    pub fn derived(error: &dyn fmt::Display, kind: Option<Kind>, code: &str, location: &'static Location<'static>) -> Nuhound {
+        let link = Nuhound::new(error).with_code(code).locate(location);
+        match kind {
+            Some(kind) => link.with_kind(kind),
+            None => link,
+        }
+    }
+
+    // Return the source field of an enum deriving `NuhoundError`
+    pub trait SourceOfBoxed<'a> {
+        fn nuhound_source(self) -> Option<&'a (dyn Error + 'static)>;
+    }
+
+    impl<'a> SourceOfBoxed<'a> for &'a Box<dyn Error> {
+        fn nuhound_source(self) -> Option<&'a (dyn Error + 'static)> {
+            Some(self.as_ref())
+        }
+    }
+
+    impl<'a> SourceOfBoxed<'a> for &'a Box<dyn Error + Send + Sync> {
+        fn nuhound_source(self) -> Option<&'a (dyn Error + 'static)> {
+            Some(self.as_ref())
         }
-        let mut current = causes.pop();
-        let mut chain = current.unwrap();
-        current = causes.pop();
-        while current.is_some() {
-            chain = current.unwrap().caused_by(chain);
-            current = causes.pop();
+    }
+
+    pub trait SourceOfError<'a> {
+        fn nuhound_source(self) -> Option<&'a (dyn Error + 'static)>;
+    }
+
+    impl<'a, E: Error + 'static> SourceOfError<'a> for &'a E {
+        fn nuhound_source(self) -> Option<&'a (dyn Error + 'static)> {
+            Some(self)
         }
+    }
 
-        // Finally add the top level message 'inform' to the chain
-        Nuhound::new(inform).caused_by(chain)
+    pub trait ChainOfError {
+        fn nuhound_chain(&self) -> Nuhound;
     }
 
-    /// Add a cause to an existing Nuhound error.
-    ///
-    /// ```
-    /// use nuhound::{Nuhound, OptionExtension};
-    ///
-    /// let error_source = vec![1, 2, 3, 4].get(4).easy().unwrap_err();
-    /// let my_error = Nuhound::new("Out of bounds").caused_by(error_source);
-    /// println!("{}", my_error.trace());
-    /// // emits:
-    /// //  0: Out of bounds
-    /// //  1: Option::None detected
-    /// ```
-    pub fn caused_by(mut self, source: Nuhound) -> Self {
-        self.source = Some(Box::new(source));
-        self
+    impl<E: Error + ?Sized> ChainOfError for &E {
+        fn nuhound_chain(&self) -> Nuhound {
+            Nuhound::from_error(self)
+        }
     }
- 
-    /// Create a list of errors starting at the most recent error and working backwards towards the
-    /// the error source.
-    ///
-    /// ```
-    /// use nuhound::{Nuhound, OptionExtension};
-    ///
-    /// let error_source = vec![1, 2, 3, 4].get(4).easy().unwrap_err();
-    /// let my_error = Nuhound::new("Out of bounds").caused_by(error_source);
-    /// println!("{}", my_error.trace());
-    /// // emits:
-    /// //  0: Out of bounds
-    /// //  1: Option::None detected
-    /// ```
-    pub fn trace(&self) -> String {
-        let mut trace_list = vec![format!(" 0: {}", self)];
-        let mut n = 1;
-        let mut item = self.source.as_ref();
-        while item.is_some() {
-            let this = item.unwrap();
-            trace_list.push(format!("{:2}: {}", n, this));
-            item = this.source.as_ref();
-            n += 1;
+}
+
+// Returns the error as a trait object that can be downcast when its type is Nuhound or io::Error,
+// the only types whose chains are recovered intact. Any other type, including one that borrows
+// data, is left to be converted by its Display text.
+fn inspectable<E: Error>(error: &E) -> Option<&(dyn Error + 'static)> {
+    if same_type::<E, Nuhound>() {
+        // SAFETY: E is Nuhound, which has no lifetime or type parameters
+        return Some(unsafe { &*(error as *const E).cast::<Nuhound>() });
+    }
+    if same_type::<E, io::Error>() {
+        // SAFETY: E is io::Error, which has no lifetime or type parameters
+        return Some(unsafe { &*(error as *const E).cast::<io::Error>() });
+    }
+    None
+}
+
+// Determines whether two types are the same. The first type need not be 'static because a type
+// id does not depend on lifetimes.
+fn same_type<A: ?Sized, B: ?Sized + 'static>() -> bool {
+    trait Identify {
+        fn type_id(&self) -> TypeId where Self: 'static;
+    }
+
+    impl<T: ?Sized> Identify for PhantomData<T> {
+        fn type_id(&self) -> TypeId where Self: 'static {
+            TypeId::of::<T>()
         }
-        trace_list.join("\n")
     }
+
+    let phantom = PhantomData::<A>;
+    let identify: &dyn Identify = &phantom;
+    // SAFETY: only the type id is read, and no value of type A is involved
+    let identify: &(dyn Identify + 'static) = unsafe { std::mem::transmute(identify) };
+    identify.type_id() == TypeId::of::<B>()
 }
 
 /// Provides `Nuhound` trait support to `std::result::Result`. Remember to `use` this if you're
 /// intending to use the `report()` and/or `easy()` methods with values of type `Result<T, E>` or
 /// functions that return `Result<T, E>`.
-pub trait ResultExtension<T, E> {
+///
+/// The error type `E` may be any type that implements [`std::error::Error`] as well as
+/// `Box<dyn Error>`, `Box<dyn Error + Send + Sync>`, `String` and `&str`. A Nuhound error, or an
+/// io error carrying one, is kept intact and an io error is given the matching [`Kind`]. The
+/// boxed forms have their source chain converted in the same way as any other error. The `M` parameter is used
+/// internally to distinguish these error types and never needs to be specified.
+///
+/// When the disclose feature is enabled, `easy()` and `report()` record the location of the caller
+/// on the error they return, unless the error already discloses a location such as one created by
+/// the `here!` macro.
+///
+/// # Example
+///
+/// ```
+/// use std::error::Error;
+/// use nuhound::{Report, here, ResultExtension};
+///
+/// fn third_party(text: &str) -> Result<u32, Box<dyn Error>> {
+///     Ok(text.parse::<u32>()?)
+/// }
+///
+/// fn legacy() -> Result<(), String> {
+///     Err("Legacy failure".to_string())
+/// }
+///
+/// fn run() -> Report<u32> {
+///     legacy().report(|e| here!(e, "Legacy code failed"))?;
+///     let value = third_party("NaN").easy()?;
+///     Ok(value)
+/// }
+///
+/// let e = run().unwrap_err();
+/// println!("{}", e.trace());
+/// ```
+pub trait ResultExtension<T, E, M = ()> {
     /// Calls op lazily if the result is Err, otherwise returns the Ok value of self.
     ///
     /// This function can be used for control flow based on result values and is similar to the
@@ -657,6 +1983,7 @@ pub trait ResultExtension<T, E> {
     /// // Display the error:
     /// // invalid digit found in string
     /// ```
+    #[track_caller]
     fn report<O: FnOnce(E) -> Nuhound>(self, op: O) -> Result<T, Nuhound>;
 
     /// Lazily converts any error into a nuhound error, otherwise returns the Ok value of self.
@@ -681,42 +2008,96 @@ pub trait ResultExtension<T, E> {
     /// // This will emit:
     /// // invalid digit found in string
     /// ```
+    #[track_caller]
     fn easy(self) -> Result<T, Nuhound>;
 }
 
 impl<T, E: Error> ResultExtension<T, E> for Result<T, E> {
+    #[track_caller]
     fn report<O: FnOnce(E) -> Nuhound>(self, op: O) -> Result<T, Nuhound> {
         match self {
             Ok(val) => Ok(val),
-            Err(e) => Err(op(e)),
+            Err(e) => Err(op(e).locate(Location::caller())),
         }
     }
 
+    #[track_caller]
     fn easy(self) -> Result<T, Nuhound> {
         match self {
             Ok(val) => Ok(val),
-            Err(e) => {
-                match e.source() {
-                    Some(source) => {
-                        let mut cause: &dyn Error = &source;
-                        let mut causes = vec![Nuhound::new(cause)];
-                        while cause.source().is_some() {
-                            cause = cause.source().unwrap();
-                            causes.push(Nuhound::new(cause));
-                        }
-
-                        let mut current = causes.pop();
-                        let mut chain = current.unwrap();
-                        current = causes.pop();
-                        while current.is_some() {
-                            chain = current.unwrap().caused_by(chain);
-                            current = causes.pop();
-                        }
-                        Err(Nuhound::new(e).caused_by(chain))
-                    },
-                    None => Err(Nuhound::new(e)),
-                }
-            },
+            Err(e) => Err(Nuhound::from_any_located(&e, Location::caller())),
+        }
+    }
+}
+
+impl<T> ResultExtension<T, Box<dyn Error>, __private::Boxed> for Result<T, Box<dyn Error>> {
+    #[track_caller]
+    fn report<O: FnOnce(Box<dyn Error>) -> Nuhound>(self, op: O) -> Result<T, Nuhound> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(op(e).locate(Location::caller())),
+        }
+    }
+
+    #[track_caller]
+    fn easy(self) -> Result<T, Nuhound> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::from_source_located(e.as_ref(), Location::caller())),
+        }
+    }
+}
+
+impl<T> ResultExtension<T, Box<dyn Error + Send + Sync>, __private::Boxed> for Result<T, Box<dyn Error + Send + Sync>> {
+    #[track_caller]
+    fn report<O: FnOnce(Box<dyn Error + Send + Sync>) -> Nuhound>(self, op: O) -> Result<T, Nuhound> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(op(e).locate(Location::caller())),
+        }
+    }
+
+    #[track_caller]
+    fn easy(self) -> Result<T, Nuhound> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::from_source_located(e.as_ref(), Location::caller())),
+        }
+    }
+}
+
+impl<T> ResultExtension<T, String, __private::Text> for Result<T, String> {
+    #[track_caller]
+    fn report<O: FnOnce(String) -> Nuhound>(self, op: O) -> Result<T, Nuhound> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(op(e).locate(Location::caller())),
+        }
+    }
+
+    #[track_caller]
+    fn easy(self) -> Result<T, Nuhound> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::new(e).locate(Location::caller())),
+        }
+    }
+}
+
+impl<'a, T> ResultExtension<T, &'a str, __private::Text> for Result<T, &'a str> {
+    #[track_caller]
+    fn report<O: FnOnce(&'a str) -> Nuhound>(self, op: O) -> Result<T, Nuhound> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(op(e).locate(Location::caller())),
+        }
+    }
+
+    #[track_caller]
+    fn easy(self) -> Result<T, Nuhound> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(Nuhound::new(e).locate(Location::caller())),
         }
     }
 }
@@ -724,6 +2105,10 @@ impl<T, E: Error> ResultExtension<T, E> for Result<T, E> {
 /// Provides `Nuhound` trait support to `std::option::Option`. Remember to `use` this if you're
 /// intending to use the `report()` and/or `easy()` methods with values of type `Option<T>` or functions that
 /// return `Option<T>`.
+///
+/// When the disclose feature is enabled, `easy()` and `report()` record the location of the caller
+/// on the error they return, unless the error already discloses a location such as one created by
+/// the `here!` macro.
 pub trait OptionExtension<T> {
     /// Transforms the `Option<T>` into a [`Result<T, Nuhound>`]
     ///
@@ -744,6 +2129,7 @@ pub trait OptionExtension<T> {
     /// let bad = oob().unwrap_err();
     /// println!("{}", bad.trace());
     /// ```
+    #[track_caller]
     fn report<O: FnOnce(Nuhound) -> Nuhound>(self, op: O) -> Result<T, Nuhound>;
 
     /// Transforms the `Option<T>` into a [`Result<T, Nuhound>`].
@@ -763,21 +2149,59 @@ pub trait OptionExtension<T> {
     /// let bad = oob().unwrap_err();
     /// println!("{bad}");
     /// ```
+    #[track_caller]
     fn easy(self) -> Result<T, Nuhound>;
+
+    /// Transforms the `Option<T>` into a [`Result<T, Nuhound>`] in the same way as `easy()` except
+    /// that `None` is reported with the given message rather than "Option::None detected".
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Report, OptionExtension};
+    ///
+    /// fn oob() -> Report<u32> {
+    ///    let list: Vec<u32> = vec![1, 2, 3, 4,];
+    ///    let bad_val = *list.get(4).easy_none("No fifth item in the list")?;
+    ///    Ok(bad_val)
+    /// }
+    /// let bad = oob().unwrap_err();
+    /// println!("{bad}");
+    /// // This will emit:
+    /// // No fifth item in the list
+    /// ```
+    #[track_caller]
+    fn easy_none(self, inform: impl fmt::Display) -> Result<T, Nuhound>
+    where
+        Self: Sized,
+    {
+        let location = Location::caller();
+        self.easy().map_err(|_| Nuhound::located(inform, location))
+    }
 }
 
 impl<T> OptionExtension<T> for Option<T> {
+    #[track_caller]
     fn report<O: FnOnce(Nuhound) -> Nuhound>(self, op: O) -> Result<T, Nuhound> {
         match self {
             Some(val) => Ok(val),
-            None => Err(op(Nuhound::new("Option::None detected"))),
+            None => Err(op(Nuhound::new("Option::None detected")).locate(Location::caller())),
         }
     }
 
+    #[track_caller]
     fn easy(self) -> Result<T, Nuhound> {
         match self {
             Some(val) => Ok(val),
-            None => Err(Nuhound::new("Option::None detected")),
+            None => Err(Nuhound::new("Option::None detected").locate(Location::caller())),
+        }
+    }
+
+    #[track_caller]
+    fn easy_none(self, inform: impl fmt::Display) -> Result<T, Nuhound> {
+        match self {
+            Some(val) => Ok(val),
+            None => Err(Nuhound::located(inform, Location::caller())),
         }
     }
 }
@@ -919,8 +2343,8 @@ mod tests {
         if cfg!(feature = "disclose") {
             let re0 = Regex::new(r"^ 0: src[\\/]lib\.rs:\d+:\d+: cannot convert string to a number$").unwrap();
             let re1 = Regex::new(r"^ 1: invalid digit found in string$").unwrap();
-            assert!(re0.is_match(&values[0]));
-            assert!(re1.is_match(&values[1]));
+            assert!(re0.is_match(values[0]));
+            assert!(re1.is_match(values[1]));
         } else {
             assert_eq!(values[0], " 0: cannot convert string to a number");
             assert_eq!(values[1], " 1: invalid digit found in string");
@@ -963,8 +2387,8 @@ mod tests {
         if cfg!(feature = "disclose") {
             let re0 = Regex::new(r"^ 0: src[\\/]lib\.rs:\d+:\d+: cannot convert string to a number$").unwrap();
             let re1 = Regex::new(r"^ 1: invalid digit found in string$").unwrap();
-            assert!(re0.is_match(&values[0]));
-            assert!(re1.is_match(&values[1]));
+            assert!(re0.is_match(values[0]));
+            assert!(re1.is_match(values[1]));
         } else {
             assert_eq!(values[0], " 0: cannot convert string to a number");
             assert_eq!(values[1], " 1: invalid digit found in string");
@@ -979,7 +2403,12 @@ mod tests {
             Ok(value)
         }
         let value = bad_value().unwrap_err().to_string(); 
-        assert_eq!(value, "invalid digit found in string");
+        if cfg!(feature = "disclose") {
+            let re = Regex::new(r"^src[\\/]lib\.rs:\d+:\d+: invalid digit found in string$").unwrap();
+            assert!(re.is_match(&value));
+        } else {
+            assert_eq!(value, "invalid digit found in string");
+        }
     }
 
     #[test]
@@ -992,6 +2421,432 @@ mod tests {
             Ok(bad_val)
         }
         let value = oob().unwrap_err().to_string(); 
-        assert_eq!(value, "Option::None detected");
+        if cfg!(feature = "disclose") {
+            let re = Regex::new(r"^src[\\/]lib\.rs:\d+:\d+: Option::None detected$").unwrap();
+            assert!(re.is_match(&value));
+        } else {
+            assert_eq!(value, "Option::None detected");
+        }
+    }
+
+    #[test]
+    fn test_11() {
+        let original = Nuhound::new("Top level failure")
+            .caused_by(Nuhound::new("Layer 2 failure")
+                .caused_by(Nuhound::new("File missing").with_kind(Kind::NotFound)));
+        let io_error = io::Error::from(original.clone());
+        assert_eq!(io_error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(io_error.to_string(), "Top level failure");
+        assert_eq!(Nuhound::from(io_error), original);
+    }
+
+    #[test]
+    fn test_12() {
+        fn header_error() -> Nuhound {
+            Nuhound::new("Bad header").with_kind(Kind::InvalidData).with_code("HDR1")
+                .caused_by(Nuhound::new("Checksum failed").with_kind(Kind::InvalidData))
+        }
+        fn read_header() -> io::Result<()> {
+            Err(header_error())?
+        }
+        fn wrapped() -> Report<()> {
+            read_header().report(|e| here!(e, "Cannot load file"))?;
+            Ok(())
+        }
+        fn easy() -> Report<()> {
+            read_header().easy()?;
+            Ok(())
+        }
+        let e = wrapped().unwrap_err();
+        let values: Vec<String> = e.trace().split('\n').map(String::from).collect();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[1], " 1: Bad header (code: HDR1, kind: InvalidData)");
+        assert_eq!(values[2], " 2: Checksum failed (kind: InvalidData)");
+        assert_eq!(e.source().unwrap().source().unwrap().downcast_ref::<Nuhound>().unwrap().kind(), Some(Kind::InvalidData));
+        let e = easy().unwrap_err();
+        assert_eq!(e.trace(), " 0: Bad header (code: HDR1, kind: InvalidData)\n 1: Checksum failed (kind: InvalidData)");
+        assert_eq!(e, header_error());
+
+        // The top link of a Nuhound error keeps its kind, code and id
+        let original = header_error();
+        let e = Err::<(), _>(original.clone()).easy().unwrap_err();
+        assert_eq!((e.kind(), e.code(), e.id()), (Some(Kind::InvalidData), Some("HDR1"), original.id()));
+        let e = here!(original.clone());
+        assert_eq!((e.kind(), e.code(), e.id()), (Some(Kind::InvalidData), Some("HDR1"), original.id()));
+        assert_eq!(e.chain().nth(1), original.chain().nth(1));
+        let e = here!(io::Error::from(original.clone()));
+        assert_eq!((e.kind(), e.code(), e.id()), (Some(Kind::InvalidData), Some("HDR1"), original.id()));
+
+        // A plain io error is given the matching kind
+        let e = Err::<(), _>(io::Error::new(io::ErrorKind::NotFound, "no such file")).easy().unwrap_err();
+        assert_eq!((e.kind(), e.message()), (Some(Kind::NotFound), "no such file"));
+        assert_eq!(here!(io::Error::from(io::ErrorKind::TimedOut)).kind(), Some(Kind::TimedOut));
+    }
+
+    #[test]
+    fn test_13() {
+        fn boxed() -> Result<u32, Box<dyn Error>> {
+            Err(Box::new(Nuhound::new("Layer 1 failure").caused_by(Nuhound::new("Root cause"))))
+        }
+        fn boxed_send() -> Result<u32, Box<dyn Error + Send + Sync>> {
+            Ok("NaN".parse::<u32>()?)
+        }
+        let e = boxed().easy().unwrap_err();
+        assert_eq!(e, Nuhound::new("Layer 1 failure").caused_by(Nuhound::new("Root cause")));
+        let e = boxed().report(|e| here!(e, "Boxed failure")).unwrap_err();
+        let values: Vec<String> = e.trace().split('\n').map(String::from).collect();
+        assert_eq!(values[1..], [" 1: Layer 1 failure", " 2: Root cause"]);
+        let e = boxed_send().easy().unwrap_err();
+        assert_eq!(e.message(), "invalid digit found in string");
+        assert_eq!(e.location().is_some(), cfg!(feature = "disclose"));
+    }
+
+    #[test]
+    fn test_14() {
+        fn owned() -> Result<u32, String> {
+            Err("Owned text failure".to_string())
+        }
+        fn borrowed() -> Result<u32, &'static str> {
+            Err("Borrowed text failure")
+        }
+        assert_eq!(owned().easy().unwrap_err().message(), "Owned text failure");
+        assert_eq!(borrowed().easy().unwrap_err().message(), "Borrowed text failure");
+        let e = borrowed().report(|e| here!(e, "Text failure")).unwrap_err();
+        let values: Vec<String> = e.trace().split('\n').map(String::from).collect();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[1], " 1: Borrowed text failure");
+    }
+
+    #[test]
+    fn test_15() {
+        fn bad_value() -> Report<u32> {
+            let value = "NaN".parse::<u32>()
+                .report(|e| Nuhound::link("cannot convert string to a number", e))?;
+            Ok(value)
+        }
+        fn layer1() -> Report<u32> {
+            let value = bad_value().easy()?;
+            Ok(value)
+        }
+        let e = bad_value().unwrap_err();
+        let value = layer1().unwrap_err().to_string();
+        if cfg!(feature = "disclose") {
+            let line = e.location().unwrap().line();
+            let re = Regex::new(&format!(r"^src[\\/]lib\.rs:{}:\d+: cannot convert string to a number$", line)).unwrap();
+            assert!(re.is_match(&e.to_string()));
+            assert!(re.is_match(&value));
+        } else {
+            assert!(e.location().is_none());
+            assert_eq!(value, "cannot convert string to a number");
+        }
+
+        // The location is held apart from the message, so a message that looks like one is kept
+        let e = Err::<(), _>("cfg.toml:3:5: bad key").easy().unwrap_err();
+        assert_eq!((e.message(), e.location().is_some()), ("cfg.toml:3:5: bad key", cfg!(feature = "disclose")));
+        let e = here!(Root, "created here");
+        assert_eq!((e.message(), e.location().is_some()), ("created here", cfg!(feature = "disclose")));
+    }
+
+    #[test]
+    fn test_16() {
+        #[context("loading config {path}")]
+        fn load_config(path: &str) -> Report<u32> {
+            if path.is_empty() {
+                return Err(Nuhound::new("No path given"));
+            }
+            let value = path.parse::<u32>().easy()?;
+            Ok(value)
+        }
+        assert_eq!(load_config("42"), Ok(42));
+        let values: Vec<String> = load_config("NaN").unwrap_err().trace().split('\n').map(String::from).collect();
+        let early = load_config("").unwrap_err().trace();
+        if cfg!(feature = "disclose") {
+            let re = Regex::new(r"^ 0: src[\\/]lib\.rs:\d+:\d+: load_config: loading config NaN$").unwrap();
+            assert!(re.is_match(&values[0]));
+            assert!(values[1].ends_with(": invalid digit found in string"));
+        } else {
+            assert_eq!(values, [" 0: load_config: loading config NaN", " 1: invalid digit found in string"]);
+        }
+        assert!(early.ends_with("load_config: loading config \n 1: No path given"));
+
+        // The message is only formatted when an error leaves the function
+        #[context("attempt {}", { calls.set(calls.get() + 1); calls.get() })]
+        fn attempt(calls: &std::cell::Cell<u8>, text: &str) -> Report<u8> {
+            let value = text.parse::<u8>().easy()?;
+            Ok(value)
+        }
+        let calls = std::cell::Cell::new(0);
+        assert_eq!((attempt(&calls, "1"), calls.get()), (Ok(1), 0));
+        assert!(attempt(&calls, "x").unwrap_err().to_string().ends_with("attempt: attempt 1"));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_17() {
+        struct Config {
+            names: Vec<String>,
+        }
+        impl Config {
+            #[context(each, "finding name {index}")]
+            fn name<'a>(&'a self, index: usize) -> Report<&'a str> {
+                let name = self.names.get(index)?;
+                let _number = "7".parse::<u8>()?;
+                Ok(name)
+            }
+        }
+        #[context(each, "reading {}", index + 1)]
+        fn first<T>(config: &Config, index: usize, marker: &T) -> Report<String> where T: std::fmt::Debug + ?Sized {
+            fn show<U: ?Sized + std::fmt::Debug>(marker: &U) -> String {
+                format!("{:?}", marker)
+            }
+            assert_eq!(show(marker), "\"x\"");
+            let name = config.name(index)?;
+            Ok(name.to_string())
+        }
+        let config = Config { names: vec!["alpha".to_string()] };
+        assert_eq!(first(&config, 0, "x"), Ok("alpha".to_string()));
+        let values: Vec<String> = first(&config, 1, "x").unwrap_err().trace().split('\n').map(String::from).collect();
+        if cfg!(feature = "disclose") {
+            assert_eq!(values.len(), 4);
+            assert!(values[0].ends_with(": first: reading 2"));
+            let re = Regex::new(r"^ 1: src[\\/]lib\.rs:\d+:\d+: propagated through first$").unwrap();
+            assert!(re.is_match(&values[1]));
+            assert!(values[2].ends_with(": name: finding name 1"));
+            let re = Regex::new(r"^ 3: src[\\/]lib\.rs:\d+:\d+: Option::None detected$").unwrap();
+            assert!(re.is_match(&values[3]));
+        } else {
+            assert_eq!(values, [" 0: first: reading 2", " 1: name: finding name 1", " 2: Option::None detected"]);
+        }
+
+        // A '?' in a nested function or closure returns from it and is left alone
+        #[context(each, "summing {text}")]
+        fn sum(text: &str) -> Report<u32> {
+            fn parse(text: &str) -> Result<u32, std::num::ParseIntError> {
+                let value = text.trim().parse::<u32>()?;
+                Ok(value)
+            }
+            let values: Result<Vec<u32>, std::num::ParseIntError> = text.split(',').map(|part| Ok(parse(part)? * 2)).collect();
+            let _checked = text.is_empty() || "0".parse::<u8>()? == 0;
+            Ok(values?.iter().sum())
+        }
+        assert_eq!(sum("1, 2"), Ok(6));
+        let e = sum("1, x").unwrap_err();
+        assert!(e.to_string().ends_with("sum: summing 1, x"));
+        assert!(e.trace().ends_with("invalid digit found in string"));
+    }
+
+    #[test]
+    fn test_18() {
+        fn parse_pair(name: &str, text: &str) -> Report<(u32, u32)> {
+            let pair = context_block!("parsing {name}", {
+                let (left, right) = text.split_once(',')?;
+                let left = left.trim().parse::<u32>()?;
+                let right = right.trim().parse::<u32>().easy()?;
+                Ok((left, right))
+            })?;
+            Ok(pair)
+        }
+        assert_eq!(parse_pair("size", "12, 4"), Ok((12, 4)));
+        let values: Vec<String> = parse_pair("size", "12, NaN").unwrap_err().trace().split('\n').map(String::from).collect();
+        let missing = parse_pair("size", "12").unwrap_err().trace();
+        if cfg!(feature = "disclose") {
+            let re = Regex::new(r"^ 0: src[\\/]lib\.rs:\d+:\d+: parsing size$").unwrap();
+            assert!(re.is_match(&values[0]));
+            assert!(values[1].ends_with(": invalid digit found in string"));
+        } else {
+            assert_eq!(values, [" 0: parsing size", " 1: invalid digit found in string"]);
+        }
+        assert_eq!(values.len(), 2);
+        assert!(missing.ends_with("Option::None detected"));
+    }
+
+    #[test]
+    fn test_19() {
+        let vector = [0, 1, 2, 3];
+        let index = 4;
+        let found = context_block!("Index {} is out of range", index - 2, { vector.get(index - 2) });
+        assert_eq!(found, Ok(&2));
+        let missing = context_block!("Index {index} is out of range", { vector.get(index) }).unwrap_err();
+        assert!(missing.to_string().ends_with("Index 4 is out of range"));
+        assert!(missing.trace().ends_with(" 1: Option::None detected"));
+        let parsed = context_block!("Parsing failed", { "NaN".parse::<u32>() }).unwrap_err();
+        assert!(parsed.trace().ends_with("Parsing failed\n 1: invalid digit found in string"));
+        let text: Result<u32, String> = Err("no value".to_string());
+        assert!(context_block!("Reading", { text }).unwrap_err().trace().ends_with(" 1: no value"));
+    }
+
+    #[test]
+    fn test_20() {
+        fn check(items: &[u32], limit: usize) -> Report<()> {
+            ensure!(!items.is_empty(), "No items were given for limit {limit}");
+            ensure!(items.len() <= limit);
+            ensure!(items.iter().all(|item| *item > 0));
+            ensure!(items[0] != Vec::<u32>::new().len() as u32 + 9, "First item is {}", items[0],);
+            ensure!(items.len() > 1 && limit < 10);
+            Ok(())
+        }
+        fn open(limit: usize) -> std::io::Result<()> {
+            ensure!(limit < 3);
+            Ok(())
+        }
+        let message = |items: &[u32], limit: usize| -> String {
+            let text = check(items, limit).unwrap_err().to_string();
+            match cfg!(feature = "disclose") {
+                true => Regex::new(r"^src[\\/]lib\.rs:\d+:\d+: ").unwrap().replace(&text, "").to_string(),
+                false => text,
+            }
+        };
+        assert_eq!(check(&[1, 2], 4), Ok(()));
+        assert_eq!(message(&[], 4), "No items were given for limit 4");
+        assert_eq!(message(&[1, 2, 3], 2), "condition failed: `items.len() <= limit` (left: 3, right: 2)");
+        assert_eq!(message(&[1, 0], 2), "condition failed: `items.iter().all(|item| *item > 0)`");
+        assert_eq!(message(&[9], 2), "First item is 9");
+        assert_eq!(message(&[1], 20), "condition failed: `items.len() > 1 && limit < 10`");
+        let e = open(3).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::Other);
+        assert!(e.to_string().ends_with("condition failed: `limit < 3` (left: 3, right: 3)"));
+    }
+
+    #[test]
+    fn test_21() {
+        fn generate_error(value: u32) -> Report<u32> {
+            if value == 23 {
+                bail!("value {value} not allowed");
+            }
+            if value == 24 {
+                bail!();
+            }
+            match "NaN".parse::<u32>() {
+                Ok(parsed) => Ok(parsed + value),
+                Err(e) => bail!(e, "parsing {} failed", "NaN"),
+            }
+        }
+        fn boxed() -> Result<(), Box<dyn Error>> {
+            bail!("boxed failure")
+        }
+        assert!(generate_error(23).unwrap_err().to_string().ends_with("value 23 not allowed"));
+        assert!(generate_error(24).unwrap_err().to_string().ends_with("unspecified error"));
+        let trace = generate_error(0).unwrap_err().trace();
+        assert!(trace.ends_with("parsing NaN failed\n 1: invalid digit found in string"));
+        assert!(boxed().unwrap_err().to_string().ends_with("boxed failure"));
+    }
+
+    #[test]
+    fn test_22() {
+        #[derive(Debug, NuhoundError)]
+        enum ConfigError {
+            #[nuhound(message = "config file {path} is missing", kind = NotFound, code = "CFG001")]
+            Missing { path: String, source: std::io::Error },
+            #[nuhound(message = "line {0} is not a number: {1}", kind = InvalidData, code = 2)]
+            Invalid(usize, #[source] std::num::ParseIntError),
+            #[nuhound(message = "{} entries", table.len())]
+            Table { table: std::collections::HashMap<u8, u8>, #[source] cause: Nuhound },
+            Boxed(#[source] Box<dyn Error + Send + Sync>),
+            Empty,
+        }
+        fn load(error: ConfigError) -> Report<()> {
+            Err(error)?;
+            Ok(())
+        }
+        let missing = ConfigError::Missing {
+            path: "app.cfg".to_string(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "no such file"),
+        };
+        assert!(missing.source().is_some());
+        let e = load(missing).unwrap_err();
+        assert_eq!((e.code(), e.kind()), (Some("CFG001"), Some(Kind::NotFound)));
+        let values: Vec<String> = e.trace().split('\n').map(String::from).collect();
+        if cfg!(feature = "disclose") {
+            let re = Regex::new(r"^ 0: src[\\/]lib\.rs:\d+:\d+: config file app.cfg is missing \(code: CFG001, kind: NotFound\)$").unwrap();
+            assert!(re.is_match(&values[0]));
+            assert!(e.to_json().contains(r#""kind":"NotFound","code":"CFG001","source":{"message":"no such file","kind":"NotFound"}"#));
+        } else {
+            assert_eq!(values, [" 0: config file app.cfg is missing (code: CFG001, kind: NotFound)", " 1: no such file (kind: NotFound)"]);
+            assert_eq!(e.to_json(), format!(r#"{{"message":"config file app.cfg is missing","id":"{}","kind":"NotFound","code":"CFG001","source":{{"message":"no such file","kind":"NotFound"}}}}"#, e.id()));
+        }
+
+        let invalid = ConfigError::Invalid(7, "x".parse::<u32>().unwrap_err());
+        assert_eq!(invalid.to_string(), "line 7 is not a number: invalid digit found in string");
+        let e = Nuhound::from(invalid);
+        assert_eq!((e.code(), e.kind()), (Some("2"), Some(Kind::InvalidData)));
+        assert!(e.trace().ends_with("(code: 2, kind: InvalidData)\n 1: invalid digit found in string"));
+
+        let cause = Nuhound::new("outer").caused_by(Nuhound::new("inner").with_code("X1"));
+        let e = Nuhound::from(ConfigError::Table { table: Default::default(), cause: cause.clone() });
+        assert_eq!(e.source, Some(Box::new(cause)));
+        assert!(e.trace().starts_with(" 0: ") && e.trace().contains("0 entries (code: ConfigError::Table)"));
+
+        let boxed = ConfigError::Boxed(Box::new(std::io::Error::other("boxed cause")));
+        assert_eq!(boxed.source().unwrap().to_string(), "boxed cause");
+        assert!(Nuhound::from(boxed).trace().ends_with("Boxed (code: ConfigError::Boxed)\n 1: boxed cause (kind: Other)"));
+
+        let e = Nuhound::from(ConfigError::Empty);
+        assert!(ConfigError::Empty.source().is_none());
+        assert!(e.trace().ends_with("Empty (code: ConfigError::Empty)"));
+    }
+
+    #[test]
+    fn test_23() {
+        fn load(id: u32) -> Report<u32> {
+            match id {
+                0 => custom!(public: format!("Profile {id} does not exist"), "no row for id {}", id),
+                _ => custom!("database offline"),
+            }
+        }
+        let e = load(0).unwrap_err();
+        assert_eq!(e.public_message(), Some("Profile 0 does not exist"));
+        assert!(e.message().ends_with("no row for id 0"));
+        assert_eq!(load(1).unwrap_err().public_message(), None);
+
+        let e = here!(e, "query failed");
+        let e = here!(e, public: "Your profile could not be loaded", "handler {} failed", "profile");
+        let e = Nuhound::new("request 9 failed").caused_by(e);
+        assert_eq!(e.render_public(), format!(
+            "An internal error occurred (ref: {0})\nYour profile could not be loaded\nAn internal error occurred (ref: {0})\nProfile 0 does not exist",
+            e.id()));
+        assert!(e.to_json().contains(r#""public":"Your profile could not be loaded""#));
+        assert!(!e.trace().contains("Your profile"));
+        let plain = here!(Root, public: "Try again later", "rate limited",);
+        assert_eq!(plain.render_public(), "Try again later");
+        assert_ne!(plain, here!(Root, "rate limited"));
+    }
+
+    #[test]
+    fn test_24() {
+        #[derive(Debug)]
+        struct Unexpected<'a>(&'a str);
+
+        impl fmt::Display for Unexpected<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "unexpected '{}'", self.0)
+            }
+        }
+
+        impl Error for Unexpected<'_> {}
+
+        fn parse(text: &str) -> Result<u32, Unexpected<'_>> {
+            text.parse().map_err(|_| Unexpected(text))
+        }
+        fn generic<E: Error>(result: Result<u32, E>) -> Report<u32> {
+            result.easy()
+        }
+
+        // Errors that borrow their input or have a generic type are accepted
+        let text = String::from("NaN");
+        let e = parse(&text).report(|e| here!(e, "Cannot parse")).unwrap_err();
+        assert_eq!(e.chain().map(Nuhound::message).collect::<Vec<_>>(), ["Cannot parse", "unexpected 'NaN'"]);
+        assert_eq!(parse(&text).easy().unwrap_err().message(), "unexpected 'NaN'");
+        assert_eq!(parse(&text).context("Cannot parse").unwrap_err().chain().count(), 2);
+        assert_eq!(parse(&text).map(Some).found().unwrap_err().message(), "unexpected 'NaN'");
+        assert_eq!(generic(parse(&text)).unwrap_err().message(), "unexpected 'NaN'");
+
+        // Nuhound and io errors are still kept intact through a generic type
+        let original = Nuhound::new("Bad header").with_kind(Kind::InvalidData).with_code("HDR1");
+        let e = generic(Err(original.clone())).unwrap_err();
+        assert_eq!((e.kind(), e.code(), e.id()), (Some(Kind::InvalidData), Some("HDR1"), original.id()));
+        let e = generic(Err(io::Error::from(original.clone()))).unwrap_err();
+        assert_eq!((e.kind(), e.code(), e.id()), (Some(Kind::InvalidData), Some("HDR1"), original.id()));
+        assert_eq!(generic(Err(io::Error::from(io::ErrorKind::TimedOut))).unwrap_err().kind(), Some(Kind::TimedOut));
     }
 }
+
@@ -0,0 +1,132 @@
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, ThreadId};
+use std::time::{Instant, SystemTime};
+
+// Whether new links capture their metadata
+static ENABLED: AtomicBool = AtomicBool::new(cfg!(feature = "metadata"));
+
+/// Details of when and where a link of an error chain was created. Metadata is only captured
+/// when enabled with the `metadata` feature or with [`Metadata::set_enabled`], and is read with
+/// `Nuhound::metadata()`. Errors compare equal whatever their metadata.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Metadata, Nuhound};
+///
+/// Metadata::set_enabled(true);
+/// let e = Nuhound::new("disk full");
+/// let metadata = e.metadata().unwrap();
+/// assert_eq!(metadata.pid(), std::process::id());
+/// assert_eq!(metadata.thread_id(), std::thread::current().id());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    timestamp: SystemTime,
+    instant: Instant,
+    thread_name: Option<String>,
+    thread_id: ThreadId,
+    pid: u32,
+}
+
+impl Metadata {
+    /// Turns the capture of metadata by new links on or off for the whole process. Capture is
+    /// on by default when the `metadata` feature is enabled.
+    pub fn set_enabled(enabled: bool) {
+        ENABLED.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns true if new links capture their metadata.
+    pub fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Relaxed)
+    }
+
+    // Returns the metadata of the current moment and thread when capture is enabled
+    pub(crate) fn capture() -> Option<Self> {
+        Metadata::is_enabled().then(Metadata::now)
+    }
+
+    // Returns the metadata of the current moment and thread
+    fn now() -> Self {
+        let thread = thread::current();
+        Metadata {
+            timestamp: SystemTime::now(),
+            instant: Instant::now(),
+            thread_name: thread.name().map(String::from),
+            thread_id: thread.id(),
+            pid: process::id(),
+        }
+    }
+
+    /// Returns the wall-clock time at which the link was created.
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// Returns the monotonic time at which the link was created.
+    pub fn instant(&self) -> Instant {
+        self.instant
+    }
+
+    /// Returns the name of the thread that created the link, if it has one.
+    pub fn thread_name(&self) -> Option<&str> {
+        self.thread_name.as_deref()
+    }
+
+    /// Returns the id of the thread that created the link.
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+
+    /// Returns the id of the process that created the link.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Nuhound;
+    use std::time::Duration;
+
+    // Creates a link holding the metadata of the current moment and thread. The capture setting
+    // is shared by the whole process, so the tests attach metadata directly rather than turn it on.
+    fn timed(inform: &str) -> Nuhound {
+        let mut e = Nuhound::new(inform);
+        e.details_mut().metadata = Some(Metadata::now());
+        e
+    }
+
+    #[test]
+    fn test_01() {
+        let root = thread::Builder::new().name("reader".into()).spawn(|| timed("read failed")).unwrap().join().unwrap();
+        thread::sleep(Duration::from_millis(20));
+        let e = timed("import failed").caused_by(root.clone());
+        let metadata = e.chain().nth(1).unwrap().metadata().unwrap();
+        assert_eq!(metadata.thread_name(), Some("reader"));
+        assert_ne!(metadata.thread_id(), thread::current().id());
+        assert!(e.metadata().unwrap().instant() - metadata.instant() >= Duration::from_millis(20));
+        assert!(e.metadata().unwrap().timestamp() >= metadata.timestamp());
+        assert_eq!(e.chain().nth(1), Some(&Nuhound::new("read failed")));
+        assert_eq!(root.metadata().map(Metadata::pid), Some(process::id()));
+    }
+
+    #[test]
+    fn test_02() {
+        let root = timed("connection reset");
+        thread::sleep(Duration::from_millis(30));
+        let middle = timed("query failed").caused_by(root);
+        let mut plain = Nuhound::new("plain");
+        plain.details_mut().metadata = None;
+        let e = Nuhound::new("request failed").caused_by(middle).with_cause(Nuhound::new("cleanup failed"));
+        let values: Vec<String> = e.trace_timed().split('\n').map(String::from).collect();
+        assert!(values[0].ends_with("request failed"));
+        assert!(values[1] == "    cause 1:" && values[2].ends_with("cleanup failed"));
+        let elapsed = values[3].strip_prefix(" 1: [+").and_then(|value| value.strip_suffix("s] query failed")).unwrap();
+        assert!(elapsed.parse::<f64>().unwrap() >= 0.030);
+        assert_eq!(values[4], " 2: [+0.000s] connection reset");
+        assert_eq!(plain.trace_timed(), " 0: plain");
+    }
+}
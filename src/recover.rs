@@ -0,0 +1,189 @@
+use crate::{Kind, Nuhound, Report};
+
+/// Provides combinators on [`Report<T>`] that handle expected failures at a higher layer. Each
+/// predicate is given every link in the error chain, from the most recent error back to the error
+/// source, and the combinator applies if any link satisfies it. The causes held by a link, such as
+/// the failures gathered by `first_ok`, `collect_all`, `retry` or a `Supervisor`, are searched in
+/// the same way straight after the link that holds them. An error that doesn't satisfy the
+/// predicate is returned unchanged, so `?`-heavy code can still handle expected failures.
+///
+/// # Example
+///
+/// ```
+/// use std::fs;
+/// use nuhound::{Context, Kind, Report, ResultExtension, ReportExtension, examine};
+///
+/// fn read_config(path: &str) -> Report<String> {
+///     let text = fs::read_to_string(path).with_context(|| format!("Reading config '{path}'"))?;
+///     Ok(text)
+/// }
+///
+/// fn start(path: &str) -> Report<String> {
+///     let text = examine!(read_config(path), "Starting the server")?;
+///     Ok(text)
+/// }
+///
+/// // A missing config file is expected, so use the default instead
+/// let text = start("xuhgd56qhsl")
+///     .recover_kind(Kind::NotFound, |_| String::from("port = 8080"))
+///     .unwrap();
+/// assert_eq!(text, "port = 8080");
+/// ```
+pub trait ReportExtension<T> {
+    /// Replaces an error that satisfies the predicate with the fallback value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Report, ResultExtension, ReportExtension};
+    ///
+    /// let value: Report<u32> = "NaN".parse::<u32>().easy();
+    /// let value = value.recover_if(|link| link.message().contains("invalid digit"), 0);
+    /// assert_eq!(value, Ok(0));
+    /// ```
+    fn recover_if<P: FnMut(&Nuhound) -> bool>(self, predicate: P, fallback: T) -> Report<T>;
+
+    /// Replaces an error with the value returned by `op` when any link in the chain is of the
+    /// given kind.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Kind, Nuhound, Report, ReportExtension};
+    ///
+    /// let value: Report<u32> = Err(Nuhound::new("Top").caused_by(Nuhound::new("Busy").with_kind(Kind::ResourceBusy)));
+    /// assert_eq!(value.recover_kind(Kind::ResourceBusy, |e| e.chain().count() as u32), Ok(2));
+    /// ```
+    fn recover_kind<O: FnOnce(Nuhound) -> T>(self, kind: Kind, op: O) -> Report<T>;
+
+    /// Calls `op` with an error that satisfies the predicate, returning its result in place of the
+    /// error. This allows an alternative that may itself fail to be tried.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Kind, Nuhound, Report, ReportExtension};
+    ///
+    /// let value: Report<u32> = Err(Nuhound::new("Timed out").with_kind(Kind::TimedOut));
+    /// let value = value.or_else_if(|link| link.kind() == Some(Kind::TimedOut), |_| Ok(42));
+    /// assert_eq!(value, Ok(42));
+    /// ```
+    fn or_else_if<P, O>(self, predicate: P, op: O) -> Report<T>
+    where
+        P: FnMut(&Nuhound) -> bool,
+        O: FnOnce(Nuhound) -> Report<T>;
+
+    /// Transforms an error that satisfies the predicate using `op`, for example to add a message
+    /// or to reclassify it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::{Kind, Nuhound, Report, ReportExtension, here};
+    ///
+    /// let value: Report<u32> = Err(Nuhound::new("Not there").with_kind(Kind::NotFound));
+    /// let value = value.map_report_if(|link| link.kind() == Some(Kind::NotFound), |e| here!(e, "Lookup failed"));
+    /// assert!(value.unwrap_err().to_string().ends_with("Lookup failed"));
+    /// ```
+    fn map_report_if<P, O>(self, predicate: P, op: O) -> Report<T>
+    where
+        P: FnMut(&Nuhound) -> bool,
+        O: FnOnce(Nuhound) -> Nuhound;
+}
+
+impl<T> ReportExtension<T> for Report<T> {
+    fn recover_if<P: FnMut(&Nuhound) -> bool>(self, predicate: P, fallback: T) -> Report<T> {
+        self.or_else_if(predicate, |_| Ok(fallback))
+    }
+
+    fn recover_kind<O: FnOnce(Nuhound) -> T>(self, kind: Kind, op: O) -> Report<T> {
+        self.or_else_if(|link| link.kind() == Some(kind), |e| Ok(op(e)))
+    }
+
+    fn or_else_if<P, O>(self, mut predicate: P, op: O) -> Report<T>
+    where
+        P: FnMut(&Nuhound) -> bool,
+        O: FnOnce(Nuhound) -> Report<T>,
+    {
+        match self {
+            Err(e) if satisfies(&e, &mut predicate) => op(e),
+            other => other,
+        }
+    }
+
+    fn map_report_if<P, O>(self, mut predicate: P, op: O) -> Report<T>
+    where
+        P: FnMut(&Nuhound) -> bool,
+        O: FnOnce(Nuhound) -> Nuhound,
+    {
+        match self {
+            Err(e) if satisfies(&e, &mut predicate) => Err(op(e)),
+            other => other,
+        }
+    }
+}
+
+// Determines whether any link of the chain, or of the causes held by its links, satisfies the
+// predicate
+fn satisfies(error: &Nuhound, predicate: &mut dyn FnMut(&Nuhound) -> bool) -> bool {
+    error.chain().any(|link| predicate(link) || link.causes().iter().any(|cause| satisfies(cause, &mut *predicate)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EachExtension, ResultExtension, examine, here};
+
+    fn layered(kind: Kind) -> Report<u32> {
+        let inner: Report<u32> = Err(Nuhound::new("Root cause").with_kind(kind));
+        let middle = examine!(inner, "Layer 2 failure");
+        examine!(middle, "Top level failure")
+    }
+
+    #[test]
+    fn test_01() {
+        assert_eq!(layered(Kind::NotFound).recover_kind(Kind::NotFound, |_| 7), Ok(7));
+        assert_eq!(layered(Kind::NotFound).recover_if(|link| link.message() == "Root cause", 8), Ok(8));
+        let e = layered(Kind::TimedOut).recover_kind(Kind::NotFound, |_| 7).unwrap_err();
+        assert_eq!(e, layered(Kind::TimedOut).unwrap_err());
+        assert_eq!(Ok(3).recover_kind(Kind::NotFound, |_| 7), Ok(3));
+    }
+
+    #[test]
+    fn test_02() {
+        let mut seen = Vec::new();
+        let e = layered(Kind::Other).or_else_if(|link| { seen.push(link.message().to_string()); false }, |_| Ok(1)).unwrap_err();
+        assert_eq!(seen.len(), 3);
+        assert!(seen[0].ends_with("Top level failure") && seen[1].ends_with("Layer 2 failure"));
+        assert_eq!(seen[2], "Root cause");
+        let retried = layered(Kind::Interrupted)
+            .or_else_if(|link| link.kind() == Some(Kind::Interrupted), |_| "NaN".parse::<u32>().easy())
+            .unwrap_err();
+        assert!(retried.trace().ends_with("invalid digit found in string"));
+        assert_eq!(retried.chain().count(), 1);
+
+        let mapped = layered(Kind::NotFound)
+            .map_report_if(|link| link.kind() == Some(Kind::NotFound), |e| here!(e, "Using defaults"))
+            .unwrap_err();
+        assert!(mapped.message().ends_with("Using defaults"));
+        assert_eq!(mapped.chain().count(), 4);
+        let unchanged = layered(Kind::Other).map_report_if(|link| link.kind() == Some(Kind::NotFound), |e| here!(e, "Unused"));
+        assert_eq!(unchanged, layered(Kind::Other));
+        assert_eq!(e.chain().count(), 3);
+    }
+
+    #[test]
+    fn test_03() {
+        let aggregated = || -> Report<Vec<u32>> {
+            [Ok(1), Err(Nuhound::new("Missing row").with_kind(Kind::NotFound))].into_iter().collect_all()
+        };
+        assert_eq!(aggregated().recover_kind(Kind::NotFound, |_| Vec::new()), Ok(Vec::new()));
+        let mut seen = Vec::new();
+        let _ = aggregated().recover_if(|link| { seen.push(link.message().to_string()); false }, Vec::new());
+        assert_eq!(seen.len(), 3);
+        assert!(seen[0].ends_with("1 of 2 items failed") && seen[1].ends_with("item 1 failed"));
+        assert_eq!(seen[2], "Missing row");
+        let e = aggregated().map_report_if(|link| link.message() == "Missing row", |e| here!(e, "Import failed")).unwrap_err();
+        assert_eq!(e.message(), "Import failed");
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Nuhound, Report};
+
+/// The source of time used by [`retry`]. The default clock uses [`Instant::now`] and
+/// [`std::thread::sleep`]; a test can supply its own clock so that it runs without waiting. A
+/// clock given to a [`RetryPolicy`] must be `Send` and `Sync` so that the policy can be shared by
+/// several threads.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Mutex;
+/// use std::time::{Duration, Instant};
+/// use nuhound::{Clock, RetryPolicy};
+///
+/// // A clock that moves forward whenever it is asked to sleep
+/// struct TestClock {
+///     start: Instant,
+///     offset: Mutex<Duration>,
+/// }
+///
+/// impl Clock for TestClock {
+///     fn now(&self) -> Instant {
+///         self.start + *self.offset.lock().unwrap()
+///     }
+///
+///     fn sleep(&self, duration: Duration) {
+///         *self.offset.lock().unwrap() += duration;
+///     }
+/// }
+///
+/// let clock = TestClock { start: Instant::now(), offset: Mutex::new(Duration::ZERO) };
+/// let policy = RetryPolicy::fixed(Duration::from_secs(1)).with_clock(clock);
+/// ```
+pub trait Clock {
+    /// Returns the current time
+    fn now(&self) -> Instant;
+
+    /// Waits for the given duration
+    fn sleep(&self, duration: Duration);
+}
+
+// The clock used unless another is given
+struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// The delay between attempts made by [`retry`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backoff {
+    /// The same delay before every retry
+    Fixed(Duration),
+    /// A delay that starts at `initial` and is multiplied by `factor` before each further retry,
+    /// up to a delay of `max`. A factor that is negative or not a finite number is taken as 1.
+    Exponential { initial: Duration, factor: f64, max: Duration },
+}
+
+/// Describes how [`retry`] repeats an operation: the delay between attempts, any jitter applied
+/// to the delay, the limits on the number of attempts and the time taken, and which errors are
+/// worth retrying. A policy allows 3 attempts and retries every error unless told otherwise. A
+/// policy is `Send` and `Sync`, so one policy can be shared by many worker threads.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use nuhound::{Kind, RetryPolicy};
+///
+/// let policy = RetryPolicy::exponential(Duration::from_millis(10), Duration::from_secs(1))
+///     .with_jitter(0.25)
+///     .with_max_attempts(5)
+///     .with_max_duration(Duration::from_secs(10))
+///     .retry_if(|e| e.chain().any(|link| link.kind() == Some(Kind::ResourceBusy)));
+/// ```
+pub struct RetryPolicy {
+    backoff: Backoff,
+    jitter: f64,
+    max_attempts: u32,
+    max_duration: Option<Duration>,
+    predicate: Box<dyn Fn(&Nuhound) -> bool + Send + Sync>,
+    clock: Box<dyn Clock + Send + Sync>,
+    seed: AtomicU64,
+}
+
+impl RetryPolicy {
+    /// Create a policy that waits for the same delay before every retry.
+    pub fn fixed(delay: Duration) -> Self {
+        RetryPolicy::new(Backoff::Fixed(delay))
+    }
+
+    /// Create a policy whose delay starts at `initial` and doubles before each further retry, up
+    /// to a delay of `max`.
+    pub fn exponential(initial: Duration, max: Duration) -> Self {
+        RetryPolicy::new(Backoff::Exponential { initial, factor: 2.0, max })
+    }
+
+    /// Create a policy with the given backoff.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use nuhound::{Backoff, RetryPolicy};
+    ///
+    /// let policy = RetryPolicy::new(Backoff::Exponential {
+    ///     initial: Duration::from_millis(50),
+    ///     factor: 1.5,
+    ///     max: Duration::from_secs(2),
+    /// });
+    /// ```
+    pub fn new(backoff: Backoff) -> Self {
+        let backoff = match backoff {
+            Backoff::Exponential { initial, factor, max } if !factor.is_finite() || factor < 0.0 => {
+                Backoff::Exponential { initial, factor: 1.0, max }
+            },
+            backoff => backoff,
+        };
+        RetryPolicy {
+            backoff,
+            jitter: 0.0,
+            max_attempts: 3,
+            max_duration: None,
+            predicate: Box::new(|_| true),
+            clock: Box::new(SystemClock),
+            seed: AtomicU64::new(RandomState::new().build_hasher().finish() | 1),
+        }
+    }
+
+    /// Reduce each delay by a random amount of up to the given fraction, between 0 and 1, so that
+    /// many clients retrying together don't do so in step. A fraction outside that range is
+    /// clamped to it and one that is not a number is taken as 0.
+    pub fn with_jitter(mut self, fraction: f64) -> Self {
+        self.jitter = if fraction.is_nan() { 0.0 } else { fraction.clamp(0.0, 1.0) };
+        self
+    }
+
+    /// Limit the number of attempts, including the first. At least one attempt is always made.
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Limit the total time taken. No retry is made if its delay would exceed the limit.
+    pub fn with_max_duration(mut self, duration: Duration) -> Self {
+        self.max_duration = Some(duration);
+        self
+    }
+
+    /// Only retry errors that satisfy the predicate. Any other error is returned at once.
+    pub fn retry_if<P: Fn(&Nuhound) -> bool + Send + Sync + 'static>(mut self, predicate: P) -> Self {
+        self.predicate = Box::new(predicate);
+        self
+    }
+
+    /// Use the given clock to measure time and to wait between attempts.
+    pub fn with_clock<C: Clock + Send + Sync + 'static>(mut self, clock: C) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Seed the jitter so that the delays are the same on every run.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.seed.store(seed | 1, Ordering::Relaxed);
+        self
+    }
+
+    // Returns the delay before the given retry, counting from 1
+    fn delay(&self, retry: u32) -> Duration {
+        let delay = match self.backoff {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, factor, max } => {
+                let delay = initial.as_secs_f64() * factor.powi(retry as i32 - 1);
+                Duration::try_from_secs_f64(delay.min(max.as_secs_f64())).unwrap_or(max)
+            },
+        };
+        Duration::try_from_secs_f64(delay.as_secs_f64() * (1.0 - self.jitter * self.random())).unwrap_or(delay)
+    }
+
+    // Returns a pseudo random number between 0 and 1 using xorshift. Threads sharing the policy
+    // each take the next number in turn.
+    fn random(&self) -> f64 {
+        let next = |mut x: u64| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        let x = match self.seed.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| Some(next(x))) {
+            Ok(x) | Err(x) => next(x),
+        };
+        (x >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+/// Runs the operation until it succeeds or the policy gives up. When it gives up, the error
+/// returned holds the error of every attempt as its causes, each labelled with the attempt number
+/// and the time elapsed since the first attempt started.
+///
+/// When the disclose feature is enabled the location of the caller is recorded on the error.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use nuhound::{Kind, Nuhound, Report, RetryPolicy, retry};
+///
+/// let mut calls = 0;
+/// let policy = RetryPolicy::fixed(Duration::from_millis(1)).with_max_attempts(4);
+/// let value = retry(&policy, || -> Report<u32> {
+///     calls += 1;
+///     match calls {
+///         3 => Ok(42),
+///         _ => Err(Nuhound::new("Lock is held").with_kind(Kind::ResourceBusy)),
+///     }
+/// });
+/// assert_eq!(value, Ok(42));
+///
+/// let e = retry(&policy, || -> Report<u32> { Err(Nuhound::new("Lock is held")) }).unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit something like:
+/// //  0: operation failed after 4 attempts in 3.2ms because the attempt limit was reached
+/// //     cause 1:
+/// //      0: attempt 1 failed after 1µs
+/// //      1: Lock is held
+/// //     cause 2:
+/// //      0: attempt 2 failed after 1.1ms
+/// //      1: Lock is held
+/// //  ...
+/// ```
+#[track_caller]
+pub fn retry<T, O: FnMut() -> Report<T>>(policy: &RetryPolicy, mut op: O) -> Report<T> {
+    let location = Location::caller();
+    let start = policy.clock.now();
+    let mut causes = Vec::new();
+    let mut attempt = 1;
+    let reason = loop {
+        let error = match op() {
+            Ok(val) => return Ok(val),
+            Err(e) => e,
+        };
+        let elapsed = policy.clock.now().duration_since(start);
+        let retryable = (policy.predicate)(&error);
+        causes.push(Nuhound::new(format!("attempt {} failed after {:?}", attempt, elapsed)).caused_by(error));
+        if !retryable {
+            break "the error is not retried";
+        }
+        if attempt >= policy.max_attempts {
+            break "the attempt limit was reached";
+        }
+        let delay = policy.delay(attempt);
+        if policy.max_duration.is_some_and(|max| elapsed.saturating_add(delay) > max) {
+            break "the time limit was reached";
+        }
+        policy.clock.sleep(delay);
+        attempt += 1;
+    };
+    let elapsed = policy.clock.now().duration_since(start);
+    let inform = format!("operation failed after {} attempt{} in {:?} because {}",
+        attempt, if attempt == 1 { "" } else { "s" }, elapsed, reason);
+    Err(causes.into_iter().fold(Nuhound::located(inform, location), Nuhound::with_cause))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Kind;
+    use std::sync::{Arc, Mutex};
+
+    // A clock that records each sleep and moves forward by the time slept
+    #[derive(Clone)]
+    struct TestClock {
+        start: Instant,
+        sleeps: Arc<Mutex<Vec<Duration>>>,
+    }
+
+    impl Clock for TestClock {
+        fn now(&self) -> Instant {
+            self.start + self.sleeps.lock().unwrap().iter().sum::<Duration>()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.sleeps.lock().unwrap().push(duration);
+        }
+    }
+
+    fn test_clock() -> TestClock {
+        TestClock { start: Instant::now(), sleeps: Default::default() }
+    }
+
+    fn busy() -> Report<u32> {
+        Err(Nuhound::new("busy").with_kind(Kind::ResourceBusy))
+    }
+
+    #[test]
+    fn test_01() {
+        let clock = test_clock();
+        let policy = RetryPolicy::exponential(Duration::from_millis(100), Duration::from_millis(300))
+            .with_max_attempts(5)
+            .with_clock(clock.clone());
+        let e = retry(&policy, busy).unwrap_err();
+        let millis: Vec<u128> = clock.sleeps.lock().unwrap().iter().map(Duration::as_millis).collect();
+        assert_eq!(millis, [100, 200, 300, 300]);
+        assert!(e.to_string().ends_with("operation failed after 5 attempts in 900ms because the attempt limit was reached"));
+        let labels: Vec<&str> = e.causes().iter().map(Nuhound::message).collect();
+        assert_eq!(labels, ["attempt 1 failed after 0ns", "attempt 2 failed after 100ms", "attempt 3 failed after 300ms",
+            "attempt 4 failed after 600ms", "attempt 5 failed after 900ms"]);
+        assert!(e.causes().iter().all(|cause| cause.chain().nth(1).unwrap().kind() == Some(Kind::ResourceBusy)));
+    }
+
+    #[test]
+    fn test_02() {
+        let clock = test_clock();
+        let policy = RetryPolicy::fixed(Duration::from_secs(1))
+            .with_max_attempts(10)
+            .with_max_duration(Duration::from_millis(2500))
+            .with_clock(clock.clone());
+        let e = retry(&policy, busy).unwrap_err();
+        assert_eq!(clock.sleeps.lock().unwrap().len(), 2);
+        assert!(e.to_string().ends_with("operation failed after 3 attempts in 2s because the time limit was reached"));
+
+        let mut calls = 0;
+        let policy = RetryPolicy::fixed(Duration::from_secs(1))
+            .retry_if(|e| e.chain().any(|link| link.kind() == Some(Kind::ResourceBusy)))
+            .with_clock(clock.clone());
+        let e = retry(&policy, || { calls += 1; Err::<u32, _>(Nuhound::new("fatal")) }).unwrap_err();
+        assert_eq!((calls, e.causes().len()), (1, 1));
+        assert!(e.to_string().ends_with("operation failed after 1 attempt in 0ns because the error is not retried"));
+        assert_eq!(retry(&policy, || Ok(3)), Ok(3));
+    }
+
+    #[test]
+    fn test_03() {
+        let delays = |seed| {
+            let clock = test_clock();
+            let policy = RetryPolicy::fixed(Duration::from_millis(1000))
+                .with_jitter(0.5)
+                .with_seed(seed)
+                .with_max_attempts(20)
+                .with_clock(clock.clone());
+            let _ = retry(&policy, busy);
+            clock.sleeps.lock().unwrap().clone()
+        };
+        let first = delays(7);
+        assert_eq!(first, delays(7));
+        assert_ne!(first, delays(8));
+        assert!(first.iter().all(|delay| *delay > Duration::from_millis(500) && *delay <= Duration::from_millis(1000)));
+    }
+
+    #[test]
+    fn test_04() {
+        let delays = |backoff, jitter| {
+            let clock = test_clock();
+            let policy = RetryPolicy::new(backoff).with_jitter(jitter).with_max_attempts(4).with_clock(clock.clone());
+            let _ = retry(&policy, busy);
+            let millis: Vec<u128> = clock.sleeps.lock().unwrap().iter().map(Duration::as_millis).collect();
+            millis
+        };
+        let exponential = |factor, max| Backoff::Exponential { initial: Duration::from_millis(100), factor, max };
+        let second = Duration::from_secs(1);
+        assert_eq!(delays(exponential(f64::NAN, second), 0.0), [100, 100, 100]);
+        assert_eq!(delays(exponential(-2.0, second), 0.0), [100, 100, 100]);
+        assert_eq!(delays(exponential(f64::INFINITY, second), 0.0), [100, 100, 100]);
+        assert_eq!(delays(Backoff::Fixed(second), f64::NAN), [1000, 1000, 1000]);
+        assert!(delays(Backoff::Fixed(second), 2.0).iter().all(|&delay| delay <= 1000));
+        assert!(delays(Backoff::Fixed(second), -1.0).iter().all(|&delay| delay == 1000));
+        assert_eq!(RetryPolicy::new(exponential(1e300, Duration::MAX)).delay(3), Duration::MAX);
+        assert!(RetryPolicy::fixed(Duration::MAX).with_jitter(0.5).delay(1) >= Duration::MAX / 2);
+    }
+
+    #[test]
+    fn test_05() {
+        let clock = test_clock();
+        let policy = RetryPolicy::fixed(Duration::from_millis(10)).with_jitter(0.5).with_max_attempts(1).with_clock(clock.clone());
+        let e = retry(&policy, busy).unwrap_err();
+        assert!(e.to_string().ends_with("operation failed after 1 attempt in 0ns because the attempt limit was reached"));
+
+        // One policy can be shared by several threads
+        let policy = RetryPolicy::fixed(Duration::from_millis(10)).with_max_attempts(2).with_clock(clock.clone());
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| assert_eq!(retry(&policy, busy).unwrap_err().causes().len(), 2));
+            }
+        });
+        assert_eq!(clock.sleeps.lock().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,191 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::panic::Location;
+use std::sync::Arc;
+
+thread_local! {
+    // The scopes entered on this thread
+    static FRAMES: RefCell<Frames> = const { RefCell::new(Frames { stack: Vec::new(), next: 0, shared: None }) };
+}
+
+// The scopes entered on a thread, outermost first, each with the identity of its guard, along
+// with a copy that is shared by every error created until the scopes change
+struct Frames {
+    stack: Vec<(u64, Frame)>,
+    next: u64,
+    shared: Option<Arc<[Frame]>>,
+}
+
+/// A scope that was entered on the thread that created a Nuhound error. It holds the description
+/// of the scope and, when the scope was entered by the [`scope!`](crate::scope!) macro with the
+/// `disclose` feature enabled, the location of the macro. A frame is displayed in the same way as
+/// an error, with any location before the description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    description: String,
+    location: Option<&'static Location<'static>>,
+}
+
+impl Frame {
+    /// Returns the description given to the scope.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the location of the macro that entered the scope, if it was recorded.
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        self.location
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.location {
+            Some(location) => write!(f, "{}:{}:{}: {}", location.file(), location.line(), location.column(), self.description),
+            None => write!(f, "{}", self.description),
+        }
+    }
+}
+
+/// A guard that describes the operation in progress on the current thread. Every Nuhound error
+/// created while the guard is alive takes a snapshot of the scopes of all the guards alive on the
+/// thread, which `trace()` shows beneath the error. The scope is removed when the guard is dropped.
+///
+/// Guards are normally created with the [`scope!`](crate::scope!) macro and dropped in the reverse
+/// of the order they were created. A guard that is dropped early removes only its own scope, so
+/// the scopes of the guards entered after it remain until those guards are dropped too.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Nuhound, ScopeGuard};
+///
+/// let guard = ScopeGuard::enter("handling request 42");
+/// let e = Nuhound::new("connection refused");
+/// drop(guard);
+/// assert_eq!(e.frames()[0].description(), "handling request 42");
+/// assert!(Nuhound::new("later").frames().is_empty());
+/// ```
+#[must_use = "the scope ends as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct ScopeGuard {
+    id: u64,
+    // Keeps the guard on the thread whose stack it changed
+    _thread: PhantomData<*const ()>,
+}
+
+impl ScopeGuard {
+    /// Enter a scope described by the given text.
+    pub fn enter(description: impl fmt::Display) -> Self {
+        ScopeGuard::entered(Frame { description: description.to_string(), location: None })
+    }
+
+    // Enters a scope that records the location of the macro that entered it
+    pub(crate) fn located(description: impl fmt::Display, location: &'static Location<'static>) -> Self {
+        ScopeGuard::entered(Frame { description: description.to_string(), location: Some(location) })
+    }
+
+    // Pushes the frame onto the stack of this thread
+    fn entered(frame: Frame) -> Self {
+        let id = FRAMES.with(|frames| {
+            let mut frames = frames.borrow_mut();
+            let id = frames.next;
+            frames.next += 1;
+            frames.stack.push((id, frame));
+            frames.shared = None;
+            id
+        });
+        ScopeGuard { id, _thread: PhantomData }
+    }
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        // The stack may already be gone while the thread is being torn down
+        let _ = FRAMES.try_with(|frames| {
+            let mut frames = frames.borrow_mut();
+            if let Some(index) = frames.stack.iter().rposition(|(id, _)| *id == self.id) {
+                frames.stack.remove(index);
+                frames.shared = None;
+            }
+        });
+    }
+}
+
+// Returns the scopes entered on this thread, outermost first, or None when there are none. The
+// scopes are only copied once for all the errors created between changes to the scopes.
+pub(crate) fn snapshot() -> Option<Arc<[Frame]>> {
+    FRAMES.try_with(|frames| {
+        let Frames { stack, shared, .. } = &mut *frames.borrow_mut();
+        match stack.is_empty() {
+            true => None,
+            false => Some(Arc::clone(shared.get_or_insert_with(|| stack.iter().map(|(_, frame)| frame.clone()).collect()))),
+        }
+    }).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Nuhound, here, scope};
+
+    fn migrate(table: &str) -> Nuhound {
+        let _guard = scope!("migrating table {table}");
+        let e = here!(Root, "disk full");
+        Nuhound::new("insert failed").caused_by(e)
+    }
+
+    // Returns the descriptions of the scopes entered on this thread
+    fn descriptions() -> Vec<String> {
+        snapshot().iter().flat_map(|frames| frames.iter()).map(|frame| frame.description().to_string()).collect()
+    }
+
+    #[test]
+    fn test_01() {
+        let e = {
+            let _guard = scope!("handling request {}", 42);
+            let e = migrate("users");
+            assert_eq!(e.frames()[1].description(), "migrating table users");
+            assert_eq!(e.frames()[1].location().is_some(), cfg!(feature = "disclose"));
+            Nuhound::new("request failed").caused_by(e)
+        };
+        let values: Vec<String> = e.trace().split('\n').map(String::from).collect();
+        assert_eq!(values.len(), 6);
+        assert_eq!(values[0], " 0: request failed");
+        assert!(values[1].starts_with("    in ") && values[1].ends_with("handling request 42"));
+        assert_eq!(values[2], " 1: insert failed");
+        assert!(values[3].ends_with("migrating table users") && values[4].ends_with("handling request 42"));
+        assert!(values[5].ends_with("disk full"));
+        assert!(snapshot().is_none());
+    }
+
+    #[test]
+    fn test_02() {
+        let outer = ScopeGuard::enter("outer");
+        let inner = ScopeGuard::enter("inner");
+        // Dropping an outer guard first ends only its own scope
+        drop(outer);
+        assert_eq!(descriptions(), ["inner"]);
+        drop(inner);
+        assert!(snapshot().is_none());
+        let _guard = ScopeGuard::enter("again");
+        assert_eq!(descriptions(), ["again"]);
+        assert!(Arc::ptr_eq(&snapshot().unwrap(), &snapshot().unwrap()));
+        let e = std::thread::spawn(|| Nuhound::new("elsewhere")).join().unwrap();
+        assert!(e.frames().is_empty());
+        assert!(Nuhound::new("here").to_json().ends_with(r#""frames":[{"description":"again"}]}"#));
+        assert_eq!(Nuhound::new("here"), std::thread::spawn(|| Nuhound::new("here")).join().unwrap());
+    }
+
+    #[test]
+    fn test_03() {
+        let location = Location::caller();
+        let _guard = ScopeGuard::located("loading", location);
+        let e = Nuhound::new("failed");
+        assert_eq!(e.frames()[0].location(), Some(location));
+        assert_eq!(e.frames()[0].to_string(), format!("{}:{}:{}: loading", location.file(), location.line(), location.column()));
+        assert!(e.trace().ends_with(&format!("in {}", e.frames()[0])));
+        assert!(e.to_json().contains(&format!(r#""frames":[{{"description":"loading","location":"{}:{}:{}"}}]"#, location.file(), location.line(), location.column())));
+    }
+}
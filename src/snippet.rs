@@ -0,0 +1,318 @@
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// The number of columns between tab stops when rendering an excerpt
+const TAB_WIDTH: usize = 4;
+
+/// A labelled byte range of the input held by a [`Snippet`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    range: Range<usize>,
+    message: String,
+}
+
+impl Label {
+    /// Returns the byte range of the input covered by the label
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the text of the label
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// A named input, such as a configuration file or a DSL script, together with labelled byte
+/// ranges that point at the cause of an error. A snippet is attached to a Nuhound error with
+/// `with_snippet()` and is rendered as an annotated excerpt of the input beneath the error by
+/// `trace()`.
+///
+/// Spans may cover several lines. Tabs are expanded and wide characters, such as those of CJK
+/// scripts, take two columns so that the underlines line up with the text. Ranges are clamped to
+/// the input and to character boundaries.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Nuhound, Snippet};
+///
+/// let text = "[server]\nport = \"eighty\"\n";
+/// let e = Nuhound::new("invalid configuration")
+///     .with_snippet(Snippet::new("app.toml", text).with_label(16..24, "expected a number"));
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: invalid configuration
+/// //     --> app.toml:2:8
+/// //       |
+/// //     2 | port = "eighty"
+/// //       |        ^^^^^^^^ expected a number
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    name: String,
+    text: Arc<str>,
+    labels: Vec<Label>,
+}
+
+impl Snippet {
+    /// Create a snippet of the named input. The text is shared between clones of the snippet.
+    pub fn new(name: impl fmt::Display, text: impl Into<Arc<str>>) -> Self {
+        Snippet {
+            name: name.to_string(),
+            text: text.into(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Add a label to the given byte range of the input
+    pub fn with_label(mut self, range: Range<usize>, message: impl fmt::Display) -> Self {
+        let start = self.boundary(range.start);
+        let end = self.boundary(range.end.max(range.start));
+        self.labels.push(Label { range: start..end, message: message.to_string() });
+        self
+    }
+
+    /// Returns the name of the input
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the text of the input
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the labels in the order they were added
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    /// Returns the line and column, both counting from 1, of a byte offset into the input. The
+    /// column counts characters rather than bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::Snippet;
+    ///
+    /// let snippet = Snippet::new("greeting", "héllo\nwörld");
+    /// assert_eq!(snippet.position(10), (2, 3));
+    /// ```
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = self.boundary(offset);
+        let line_start = self.text[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let line = self.text[..offset].matches('\n').count() + 1;
+        (line, self.text[line_start..offset].chars().count() + 1)
+    }
+
+    /// Renders the lines of the input covered by the labels with each labelled range underlined.
+    /// Spans of more than four lines show only their first two and last lines.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::Snippet;
+    ///
+    /// let text = "let x = (1 +\n    2;\n";
+    /// let snippet = Snippet::new("script", text).with_label(8..18, "unclosed parenthesis");
+    /// assert_eq!(snippet.render(), [
+    ///     "--> script:1:9",
+    ///     "  |",
+    ///     "1 | let x = (1 +",
+    ///     "  |         ^^^^",
+    ///     "2 |     2;",
+    ///     "  | ^^^^^ unclosed parenthesis",
+    /// ].join("\n"));
+    /// ```
+    pub fn render(&self) -> String {
+        let mut labels: Vec<&Label> = self.labels.iter().collect();
+        labels.sort_by_key(|label| (label.range.start, label.range.end));
+        let first = match labels.first() {
+            Some(first) => first,
+            None => return format!("--> {}", self.name),
+        };
+        let (line, column) = self.position(first.range.start);
+        let mut rendered = vec![format!("--> {}:{}:{}", self.name, line, column)];
+
+        // Collect the lines to show, numbered from 0
+        let mut shown = Vec::new();
+        for label in &labels {
+            let (start, end) = self.lines_of(label);
+            match end - start {
+                0..=3 => shown.extend(start..=end),
+                _ => shown.extend([start, start + 1, end]),
+            }
+        }
+        shown.sort_unstable();
+        shown.dedup();
+
+        let width = (shown.last().unwrap() + 1).to_string().len();
+        let gutter = " ".repeat(width);
+        rendered.push(format!("{} |", gutter));
+        let lines: Vec<&str> = self.text.split('\n').collect();
+        let mut previous = None;
+        for number in shown {
+            if previous.is_some_and(|previous| number > previous + 1) {
+                rendered.push(format!("{} ...", gutter));
+            }
+            previous = Some(number);
+            let line = lines[number].strip_suffix('\r').unwrap_or(lines[number]);
+            rendered.push(format!("{:>width$} | {}", number + 1, expand(line), width = width).trim_end().to_string());
+            let line_start = self.line_start(number);
+            let line_end = line_start + line.len();
+            for label in &labels {
+                let (start, end) = self.lines_of(label);
+                if number < start || number > end {
+                    continue;
+                }
+                let from = label.range.start.max(line_start) - line_start;
+                let to = label.range.end.min(line_end).max(line_start) - line_start;
+                let column = columns(&line[..from]);
+                let length = columns(&line[..to.max(from)]).saturating_sub(column).max(1);
+                let mut underline = format!("{} | {}{}", gutter, " ".repeat(column), "^".repeat(length));
+                if number == end {
+                    underline.push(' ');
+                    underline.push_str(&label.message);
+                }
+                rendered.push(underline.trim_end().to_string());
+            }
+        }
+        rendered.join("\n")
+    }
+
+    // Moves an offset back to the nearest character boundary within the input
+    fn boundary(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+
+    // Returns the first and last lines, numbered from 0, covered by a label
+    fn lines_of(&self, label: &Label) -> (usize, usize) {
+        let line_of = |offset: usize| self.text[..offset].matches('\n').count();
+        let last = match label.range.is_empty() {
+            true => label.range.start,
+            false => label.range.end - 1,
+        };
+        (line_of(label.range.start), line_of(last))
+    }
+
+    // Returns the byte offset of the start of a line numbered from 0
+    fn line_start(&self, number: usize) -> usize {
+        match number {
+            0 => 0,
+            _ => self.text.match_indices('\n').nth(number - 1).map_or(self.text.len(), |(index, _)| index + 1),
+        }
+    }
+}
+
+// Returns the number of columns taken by the text when tabs are expanded
+fn columns(text: &str) -> usize {
+    text.chars().fold(0, |column, ch| match ch {
+        '\t' => column + TAB_WIDTH - column % TAB_WIDTH,
+        ch => column + width(ch),
+    })
+}
+
+// Replaces tabs with spaces up to the next tab stop
+fn expand(text: &str) -> String {
+    let mut expanded = String::new();
+    let mut column = 0;
+    for ch in text.chars() {
+        match ch {
+            '\t' => {
+                let spaces = TAB_WIDTH - column % TAB_WIDTH;
+                expanded.push_str(&" ".repeat(spaces));
+                column += spaces;
+            },
+            ch => {
+                expanded.push(ch);
+                column += width(ch);
+            },
+        }
+    }
+    expanded
+}
+
+// Returns the number of columns a character takes on a terminal. Combining marks take none and
+// the wide characters of East Asian scripts take two.
+fn width(ch: char) -> usize {
+    match ch as u32 {
+        0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F | 0x2E80..=0x303E | 0x3041..=0x33FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6 | 0x1F300..=0x1F64F | 0x1F900..=0x1F9FF | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Nuhound;
+
+    #[test]
+    fn test_01() {
+        let text = "a = 1\n\tb = \"x\"\nname = \"日本\" # 名前\n";
+        let snippet = Snippet::new("input.cfg", text)
+            .with_label(11..14, "not a number")
+            .with_label(7..8, "tab before key")
+            .with_label(text.find('#').unwrap()..text.len() + 5, "comment");
+        assert_eq!(snippet.render(), [
+            "--> input.cfg:2:2",
+            "  |",
+            "2 |     b = \"x\"",
+            "  |     ^ tab before key",
+            "  |         ^^^ not a number",
+            "3 | name = \"日本\" # 名前",
+            "  |               ^^^^^^ comment",
+        ].join("\n"));
+        assert_eq!(snippet.position(text.find('#').unwrap()), (3, 13));
+    }
+
+    #[test]
+    fn test_02() {
+        let text = (1..=12).map(|n| format!("line {n}")).collect::<Vec<String>>().join("\n");
+        let start = text.find("line 3").unwrap();
+        let end = text.find("line 9").unwrap() + 4;
+        let snippet = Snippet::new("long", text.as_str()).with_label(start..end, "block").with_label(text.len()..text.len(), "end");
+        assert_eq!(snippet.render(), [
+            "--> long:3:1",
+            "   |",
+            " 3 | line 3",
+            "   | ^^^^^^",
+            " 4 | line 4",
+            "   | ^^^^^^",
+            "   ...",
+            " 9 | line 9",
+            "   | ^^^^ block",
+            "   ...",
+            "12 | line 12",
+            "   |        ^ end",
+        ].join("\n"));
+        assert_eq!(Snippet::new("empty", "").render(), "--> empty");
+    }
+
+    #[test]
+    fn test_03() {
+        let snippet = Snippet::new("app.toml", "port = \"eighty\"").with_label(7..15, "expected a number");
+        let e = Nuhound::new("invalid configuration").with_snippet(snippet.clone());
+        let e = Nuhound::new("loading failed").caused_by(e);
+        assert_eq!(e.trace(), [
+            " 0: loading failed",
+            " 1: invalid configuration",
+            "    --> app.toml:1:8",
+            "      |",
+            "    1 | port = \"eighty\"",
+            "      |        ^^^^^^^^ expected a number",
+        ].join("\n"));
+        assert_eq!(e.chain().nth(1).unwrap().snippet(), Some(&snippet));
+        assert!(e.to_json().ends_with(r#""snippet":{"name":"app.toml","labels":[{"start":7,"end":15,"line":1,"column":8,"message":"expected a number"}]}}}"#));
+        let clamped = Snippet::new("short", "é").with_label(1..40, "clamped");
+        assert_eq!(clamped.labels()[0].range(), 0..2);
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::Location;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{JoinExtension, Nuhound, Report, ResultExtension};
+
+/// Decides when a [`Supervisor`] gives up on its workers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SupervisorPolicy {
+    /// Stop the workers at the first error
+    FailFast,
+    /// Let every worker run to the end and fail if any error was reported
+    CollectAll,
+    /// Stop the workers once the given number of errors have been reported within the window.
+    /// Fewer errors are tolerated and returned by `finish()`. A threshold of no errors is taken
+    /// as `FailFast`.
+    Threshold { errors: usize, window: Duration },
+}
+
+// An error reported by a worker
+#[derive(Debug, Clone)]
+struct Failure {
+    worker: String,
+    error: Nuhound,
+}
+
+/// A handle given to a worker for reporting errors to its [`Supervisor`]. Reporters can be cloned
+/// and sent to other threads; the clones report as the same worker unless renamed with
+/// `for_worker()`.
+#[derive(Debug, Clone)]
+pub struct Reporter {
+    worker: String,
+    state: Arc<Mutex<State>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Reporter {
+    /// Reports an error on behalf of the worker and applies the policy of the supervisor to it.
+    /// Errors reported after the supervisor has finished are discarded.
+    pub fn report(&self, error: Nuhound) {
+        lock(&self.state).receive(Failure { worker: self.worker.clone(), error });
+    }
+
+    /// Reports the error of a result and returns its value, if any.
+    pub fn report_result<T>(&self, report: Report<T>) -> Option<T> {
+        report.map_err(|e| self.report(e)).ok()
+    }
+
+    /// Returns a reporter for another worker of the same supervisor.
+    pub fn for_worker(&self, worker: impl fmt::Display) -> Self {
+        Reporter { worker: worker.to_string(), ..self.clone() }
+    }
+
+    /// Returns the name of the worker.
+    pub fn worker(&self) -> &str {
+        &self.worker
+    }
+
+    /// Returns true once the supervisor has applied its policy and given up on the workers.
+    /// Long running workers should check this regularly and return when it is set.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+}
+
+/// Collects the errors of a pool of worker threads and applies a [`SupervisorPolicy`] to them.
+/// Workers are either spawned by the supervisor or given a [`Reporter`]. When the supervisor is
+/// finished it returns a single Nuhound error whose causes hold every error labelled with the
+/// name of the worker that reported it.
+///
+/// The policy is applied as each error is reported, so a policy that gives up sets the stop flag
+/// seen by every reporter while the remaining workers are still running.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, Supervisor, SupervisorPolicy, here};
+///
+/// let mut supervisor = Supervisor::new(SupervisorPolicy::CollectAll);
+/// for shard in 0..3 {
+///     supervisor.spawn(format!("shard-{shard}"), move |reporter| -> Report<()> {
+///         if shard == 1 {
+///             reporter.report(here!(Root, "row 17 is corrupt"));
+///             return Err(here!(Root, "index rebuild failed"));
+///         }
+///         Ok(())
+///     }).unwrap();
+/// }
+/// let e = supervisor.finish().unwrap_err();
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: 2 errors reported by worker 'shard-1'
+/// //     cause 1:
+/// //      0: worker 'shard-1' failed
+/// //      1: row 17 is corrupt
+/// //     cause 2:
+/// //      0: worker 'shard-1' failed
+/// //      1: index rebuild failed
+/// ```
+#[derive(Debug)]
+pub struct Supervisor {
+    state: Arc<Mutex<State>>,
+    stopped: Arc<AtomicBool>,
+    handles: Vec<(String, JoinHandle<Report<()>>)>,
+}
+
+// The failures received by a supervisor and the policy applied to them
+#[derive(Debug)]
+struct State {
+    policy: SupervisorPolicy,
+    stopped: Arc<AtomicBool>,
+    failures: Vec<Failure>,
+    // The times of the latest failures, oldest first, as many as a threshold policy counts
+    recent: VecDeque<Instant>,
+}
+
+impl Supervisor {
+    /// Create a supervisor that applies the given policy.
+    pub fn new(policy: SupervisorPolicy) -> Self {
+        let policy = match policy {
+            SupervisorPolicy::Threshold { errors: 0, .. } => SupervisorPolicy::FailFast,
+            policy => policy,
+        };
+        let stopped = Arc::new(AtomicBool::new(false));
+        let state = State { policy, stopped: Arc::clone(&stopped), failures: Vec::new(), recent: VecDeque::new() };
+        Supervisor { state: Arc::new(Mutex::new(state)), stopped, handles: Vec::new() }
+    }
+
+    /// Returns a reporter for the named worker.
+    pub fn reporter(&self, worker: impl fmt::Display) -> Reporter {
+        Reporter {
+            worker: worker.to_string(),
+            state: Arc::clone(&self.state),
+            stopped: Arc::clone(&self.stopped),
+        }
+    }
+
+    /// Runs the operation on a new thread with the name of the worker. The operation is given a
+    /// reporter for the worker and any error it returns, or any panic, is reported as well.
+    pub fn spawn<F>(&mut self, worker: impl fmt::Display, op: F) -> Report<()>
+    where
+        F: FnOnce(Reporter) -> Report<()> + Send + 'static,
+    {
+        let reporter = self.reporter(worker);
+        let worker = reporter.worker.clone();
+        let handle = thread::Builder::new().name(worker.clone()).spawn(move || op(reporter)).easy()?;
+        self.handles.push((worker, handle));
+        Ok(())
+    }
+
+    /// Returns true once the policy has given up on the workers.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Returns an error holding the errors reported so far if the policy has given up on the
+    /// workers.
+    #[track_caller]
+    pub fn check(&self) -> Report<()> {
+        let location = Location::caller();
+        let state = lock(&self.state);
+        match state.is_stopped() {
+            true => Err(state.aggregate(location)),
+            false => Ok(()),
+        }
+    }
+
+    /// Waits for every spawned worker to finish and then takes the errors reported so far. Returns
+    /// the errors tolerated by a threshold policy, or a single error whose causes hold every error
+    /// reported.
+    ///
+    /// The supervisor doesn't wait for reporters handed out by `reporter()`, so threads that were
+    /// not spawned by the supervisor should be joined before calling this. Errors they report
+    /// afterwards are discarded.
+    #[track_caller]
+    pub fn finish(self) -> Report<Vec<Nuhound>> {
+        let location = Location::caller();
+        // The workers are joined before taking the lock because they may still be reporting
+        let results: Vec<(String, Report<Report<()>>)> = self.handles.into_iter()
+            .map(|(worker, handle)| (worker, handle.join_easy()))
+            .collect();
+        let mut state = lock(&self.state);
+        for (worker, result) in results {
+            if let Err(e) | Ok(Err(e)) = result {
+                state.receive(Failure { worker, error: e });
+            }
+        }
+        match (state.policy, state.is_stopped()) {
+            (SupervisorPolicy::Threshold { .. }, false) => Ok(state.failures.drain(..).map(labelled).collect()),
+            (_, false) if state.failures.is_empty() => Ok(Vec::new()),
+            _ => Err(state.aggregate(location)),
+        }
+    }
+}
+
+impl State {
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    // Records a failure and gives up on the workers if the policy says so
+    fn receive(&mut self, failure: Failure) {
+        self.failures.push(failure);
+        let stop = match self.policy {
+            SupervisorPolicy::FailFast => true,
+            SupervisorPolicy::CollectAll => false,
+            SupervisorPolicy::Threshold { errors, window } => {
+                // Failures are received under the lock, so their times arrive in order
+                if self.recent.len() == errors {
+                    self.recent.pop_front();
+                }
+                self.recent.push_back(Instant::now());
+                self.recent.len() == errors && self.recent[errors - 1] - self.recent[0] <= window
+            },
+        };
+        if stop {
+            self.stopped.store(true, Ordering::Relaxed);
+        }
+    }
+    // Gathers every failure into the causes of a single error
+    fn aggregate(&self, location: &'static Location<'static>) -> Nuhound {
+        let mut workers: Vec<&str> = Vec::new();
+        for failure in &self.failures {
+            if !workers.contains(&failure.worker.as_str()) {
+                workers.push(&failure.worker);
+            }
+        }
+        let count = self.failures.len();
+        let reported = format!("{} error{} reported by worker{} {}",
+            count,
+            if count == 1 { "" } else { "s" },
+            if workers.len() == 1 { "" } else { "s" },
+            workers.iter().map(|worker| format!("'{}'", worker)).collect::<Vec<String>>().join(", "));
+        let inform = match (self.policy, self.is_stopped()) {
+            (SupervisorPolicy::FailFast, true) => format!("stopped at the first error; {}", reported),
+            (SupervisorPolicy::Threshold { errors, window }, true) => format!("stopped after {} errors within {:?}; {}", errors, window, reported),
+            _ => reported,
+        };
+        self.failures.iter().cloned().map(labelled).fold(Nuhound::located(inform, location), Nuhound::with_cause)
+    }
+}
+
+// Locks the state, which stays usable even if a thread panicked while holding the lock
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Names the worker that reported a failure
+fn labelled(failure: Failure) -> Nuhound {
+    Nuhound::new(format!("worker '{}' failed", failure.worker)).caused_by(failure.error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic;
+
+    #[test]
+    fn test_01() {
+        let mut supervisor = Supervisor::new(SupervisorPolicy::FailFast);
+        let reporter = supervisor.reporter("main");
+        supervisor.spawn("looper", |reporter| {
+            while !reporter.is_stopped() {
+                thread::yield_now();
+            }
+            Ok(())
+        }).unwrap();
+        assert!(supervisor.check().is_ok());
+        reporter.for_worker("checker").report(Nuhound::new("checksum mismatch"));
+        assert!(reporter.is_stopped() && supervisor.is_stopped());
+        assert_eq!(reporter.report_result(Ok::<u8, Nuhound>(2)), Some(2));
+        drop(reporter);
+        let e = supervisor.finish().unwrap_err();
+        let values: Vec<String> = e.trace().split('\n').map(String::from).collect();
+        assert!(values[0].ends_with("stopped at the first error; 1 error reported by worker 'checker'"));
+        assert_eq!(values[1..], ["    cause 1:", "     0: worker 'checker' failed", "     1: checksum mismatch"]);
+    }
+
+    #[test]
+    fn test_02() {
+        let mut supervisor = Supervisor::new(SupervisorPolicy::CollectAll);
+        supervisor.spawn("panicker", |_| -> Report<()> { panic::panic_any(Nuhound::new("poisoned")) }).unwrap();
+        let reporter = supervisor.reporter("a");
+        supervisor.spawn("b", move |_| {
+            reporter.report(Nuhound::new("first"));
+            Err(Nuhound::new("second"))
+        }).unwrap();
+        assert!(supervisor.check().is_ok());
+        let e = supervisor.finish().unwrap_err();
+        assert!(e.to_string().contains("3 errors reported by workers "));
+        let mut causes: Vec<String> = e.causes().iter().map(|cause| cause.chain().map(|link| link.message().to_string()).collect::<Vec<String>>().join(" <- ")).collect();
+        causes.sort();
+        assert_eq!(causes[0], "worker 'a' failed <- first");
+        assert_eq!(causes[1], "worker 'b' failed <- second");
+        assert!(causes[2].starts_with("worker 'panicker' failed <- ") && causes[2].ends_with("thread 'panicker' panicked <- poisoned"));
+        assert_eq!(Supervisor::new(SupervisorPolicy::CollectAll).finish(), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_03() {
+        let tolerant = Supervisor::new(SupervisorPolicy::Threshold { errors: 2, window: Duration::ZERO });
+        let reporter = tolerant.reporter("flaky");
+        reporter.report(Nuhound::new("one"));
+        thread::sleep(Duration::from_millis(2));
+        reporter.report(Nuhound::new("two"));
+        drop(reporter);
+        let tolerated = tolerant.finish().unwrap();
+        assert_eq!(tolerated.len(), 2);
+        assert_eq!(tolerated[1].chain().nth(1).unwrap().message(), "two");
+
+        let mut strict = Supervisor::new(SupervisorPolicy::Threshold { errors: 2, window: Duration::from_secs(3600) });
+        let reporter = strict.reporter("flaky");
+        reporter.report(Nuhound::new("one"));
+        assert!(strict.check().is_ok());
+        reporter.report(Nuhound::new("two"));
+        assert!(strict.check().unwrap_err().to_string().ends_with("stopped after 2 errors within 3600s; 2 errors reported by worker 'flaky'"));
+        assert!(reporter.is_stopped());
+
+        let reporter = Supervisor::new(SupervisorPolicy::Threshold { errors: 0, window: Duration::ZERO }).reporter("any");
+        reporter.report(Nuhound::new("one"));
+        assert!(reporter.is_stopped());
+    }
+
+    #[test]
+    fn test_04() {
+        // A reporter that is still held, or leaked, doesn't keep the supervisor waiting
+        let mut supervisor = Supervisor::new(SupervisorPolicy::CollectAll);
+        let held = supervisor.reporter("main");
+        std::mem::forget(supervisor.reporter("leaked"));
+        held.report(Nuhound::new("bad input"));
+        supervisor.spawn("worker", |reporter| {
+            reporter.report(Nuhound::new("slow disk"));
+            thread::sleep(Duration::from_millis(20));
+            Err(Nuhound::new("gave up"))
+        }).unwrap();
+        let e = supervisor.finish().unwrap_err();
+        assert!(e.to_string().ends_with("3 errors reported by workers 'main', 'worker'"));
+        held.report(Nuhound::new("too late"));
+        assert!(!held.is_stopped());
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::panic::Location;
+
+use crate::{Nuhound, Report};
+
+// A step in the path to a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+/// Collects every validation failure of a nested value, such as a configuration file or an API
+/// payload, each under the path of the field that failed, for example `servers[2].tls.cert_path`.
+///
+/// The path is built with the `push_field()` and `push_index()` scopes, which remove their step
+/// from the path when they are dropped. Failures are recorded with the `validate!` macro or the
+/// `add()`, `error()` and `extend_from()` methods. The `finish()` method returns a single Nuhound
+/// error whose causes are the failures, each holding its path in a field called `path`.
+///
+/// # Example
+///
+/// ```
+/// use nuhound::{Report, Validator, validate};
+///
+/// struct Server {
+///     host: String,
+///     cert_path: String,
+/// }
+///
+/// fn check(servers: &[Server]) -> Report<()> {
+///     let mut validator = Validator::new();
+///     let mut list = validator.push_field("servers");
+///     for (index, server) in servers.iter().enumerate() {
+///         let mut item = list.push_index(index);
+///         validate!(item.push_field("host"), !server.host.is_empty(), "must not be empty");
+///         let mut tls = item.push_field("tls");
+///         validate!(tls.push_field("cert_path"), server.cert_path.ends_with(".pem"), "must be a .pem file");
+///     }
+///     drop(list);
+///     validator.finish()
+/// }
+///
+/// let servers = [
+///     Server { host: "a".to_string(), cert_path: "a.pem".to_string() },
+///     Server { host: "".to_string(), cert_path: "b.txt".to_string() },
+/// ];
+/// let e = check(&servers).unwrap_err();
+/// assert_eq!(e.causes()[1].field("path"), Some("servers[1].tls.cert_path"));
+/// println!("{}", e.trace());
+/// // This will emit:
+/// //  0: validation failed with 2 errors
+/// //     cause 1:
+/// //      0: must not be empty (path: servers[1].host)
+/// //     cause 2:
+/// //      0: must be a .pem file (path: servers[1].tls.cert_path)
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validator {
+    path: Vec<Segment>,
+    errors: Vec<Nuhound>,
+}
+
+/// A step in the path of a [`Validator`] that is removed when dropped. A scope can be used in
+/// place of the validator itself, including to push further steps.
+#[derive(Debug)]
+pub struct PathScope<'a> {
+    validator: &'a mut Validator,
+}
+
+impl Deref for PathScope<'_> {
+    type Target = Validator;
+
+    fn deref(&self) -> &Validator {
+        self.validator
+    }
+}
+
+impl DerefMut for PathScope<'_> {
+    fn deref_mut(&mut self) -> &mut Validator {
+        self.validator
+    }
+}
+
+impl Drop for PathScope<'_> {
+    fn drop(&mut self) {
+        self.validator.path.pop();
+    }
+}
+
+impl Validator {
+    /// Create a validator with an empty path
+    pub fn new() -> Self {
+        Validator::default()
+    }
+
+    /// Add a named field to the path until the returned scope is dropped
+    pub fn push_field(&mut self, name: impl fmt::Display) -> PathScope<'_> {
+        self.path.push(Segment::Field(name.to_string()));
+        PathScope { validator: self }
+    }
+
+    /// Add an index to the path until the returned scope is dropped
+    pub fn push_index(&mut self, index: usize) -> PathScope<'_> {
+        self.path.push(Segment::Index(index));
+        PathScope { validator: self }
+    }
+
+    /// Returns the current path, such as `servers[2].tls`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nuhound::Validator;
+    ///
+    /// let mut validator = Validator::new();
+    /// let mut servers = validator.push_field("servers");
+    /// let mut server = servers.push_index(2);
+    /// assert_eq!(server.push_field("tls").path(), "servers[2].tls");
+    /// ```
+    pub fn path(&self) -> String {
+        let mut path = String::new();
+        for segment in &self.path {
+            match segment {
+                Segment::Field(name) if path.is_empty() => path.push_str(name),
+                Segment::Field(name) => {
+                    path.push('.');
+                    path.push_str(name);
+                },
+                Segment::Index(index) => path.push_str(&format!("[{}]", index)),
+            }
+        }
+        path
+    }
+
+    /// Record an error under the current path
+    pub fn add(&mut self, error: Nuhound) {
+        let error = match self.path.is_empty() {
+            true => error,
+            false => error.with_field("path", self.path()),
+        };
+        self.errors.push(error);
+    }
+
+    /// Record an error with the given message under the current path. When the disclose feature
+    /// is enabled the location of the caller is recorded on the error.
+    #[track_caller]
+    pub fn error(&mut self, inform: impl fmt::Display) {
+        self.add(Nuhound::located(inform, Location::caller()));
+    }
+
+    /// Returns the value of a successful report or records its error under the current path,
+    /// returning `None`.
+    pub fn extend_from<T>(&mut self, report: Report<T>) -> Option<T> {
+        match report {
+            Ok(val) => Some(val),
+            Err(e) => {
+                self.add(e);
+                None
+            },
+        }
+    }
+
+    /// Determines whether no errors have been recorded
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the errors recorded so far
+    pub fn errors(&self) -> &[Nuhound] {
+        &self.errors
+    }
+
+    /// Returns `Ok(())` if no errors have been recorded, otherwise an error whose causes are the
+    /// recorded errors.
+    #[track_caller]
+    pub fn finish(&mut self) -> Report<()> {
+        self.finish_with(())
+    }
+
+    /// Returns the value if no errors have been recorded, otherwise an error whose causes are the
+    /// recorded errors.
+    #[track_caller]
+    pub fn finish_with<T>(&mut self, value: T) -> Report<T> {
+        if self.errors.is_empty() {
+            return Ok(value);
+        }
+        let count = self.errors.len();
+        let inform = format!("validation failed with {} error{}", count, if count == 1 { "" } else { "s" });
+        let errors = std::mem::take(&mut self.errors);
+        Err(errors.into_iter().fold(Nuhound::located(inform, Location::caller()), Nuhound::with_cause))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ResultExtension, validate};
+    use regex::Regex;
+
+    #[test]
+    fn test_01() {
+        let mut validator = Validator::new();
+        assert!(validate!(validator, 1 < 2));
+        {
+            let mut servers = validator.push_field("servers");
+            let mut server = servers.push_index(2);
+            assert!(!validate!(server.push_field("port"), "70000".parse::<u16>().is_ok()));
+            let mut tls = server.push_field("tls");
+            tls.push_field("cert_path").error("missing");
+            let mut matrix = tls.push_field("matrix");
+            matrix.push_index(0).push_index(1).extend_from::<u8>("x".parse::<u8>().easy());
+            assert_eq!(matrix.path(), "servers[2].tls.matrix");
+        }
+        assert_eq!(validator.path(), "");
+        validator.error("top level");
+        let paths: Vec<Option<&str>> = validator.errors().iter().map(|e| e.field("path")).collect();
+        assert_eq!(paths, [Some("servers[2].port"), Some("servers[2].tls.cert_path"), Some("servers[2].tls.matrix[0][1]"), None]);
+        let e = validator.finish_with(1).unwrap_err();
+        assert!(validator.is_valid());
+        assert_eq!(validator.finish_with(2), Ok(2));
+        assert!(e.message().ends_with("validation failed with 4 errors"));
+        assert!(e.causes()[0].trace().ends_with("condition failed: `\"70000\".parse::<u16>().is_ok()` (path: servers[2].port)"));
+        assert!(e.to_json().contains(r#""fields":{"path":"servers[2].tls.cert_path"}"#));
+    }
+
+    #[test]
+    fn test_02() {
+        let mut validator = Validator::new();
+        validate!(validator.push_field("name"), false, "name {} is invalid", "x");
+        let e = validator.finish().unwrap_err();
+        if cfg!(feature = "disclose") {
+            let re = Regex::new(r"^ 0: src[\\/]validator\.rs:\d+:\d+: name x is invalid \(path: name\)$").unwrap();
+            assert!(re.is_match(&e.causes()[0].trace()));
+        } else {
+            assert_eq!(e.trace(), " 0: validation failed with 1 error\n    cause 1:\n     0: name x is invalid (path: name)");
+        }
+    }
+}